@@ -1,8 +1,12 @@
-use lambda_http::{Body, Error, Request, Response, tracing};
+use lambda_http::{Body, Error, Request, RequestExt, Response, tracing};
 use aws_config::BehaviorVersion;
 use aws_sdk_secretsmanager::Client as SMClient;
 use serde::Deserialize;
 use serde_json::Value;
+use crate::interactivity::{
+    deferred_request, form_payload, handle_block_actions, invoke_deferred, BlockActionsPayload, DEFERRED_HEADER,
+};
+use crate::signature::verify_slack_signature;
 use crate::slack_client::post_slack_message;
 
 #[derive(Deserialize, Debug)]
@@ -65,7 +69,7 @@ async fn event_app_mention_handler(event: &SlackEvent, token: &str) -> Result<()
     tracing::info!("Text: {}", clean_text);
     tracing::info!("Channel: {}", event.channel);
     
-    match post_slack_message(&token, &event.channel, &clean_text).await {
+    match post_slack_message(token, &event.channel, &clean_text).await {
         Ok(_) => Ok(()),
         Err(e) => {
             let err_msg = format!("Failed to post message to Slack: {}", e);
@@ -83,6 +87,11 @@ pub(crate) async fn function_handler(event: Request) -> Result<Response<Body>, E
         _ => return Err("Unsupported body type".into()),
     };
 
+    // Button clicks arrive form-encoded, with the interaction as JSON in `payload`
+    if let Some(payload) = form_payload(body_str) {
+        return interactivity_handler(&event, body_str, payload).await;
+    }
+
     let payload: Value = serde_json::from_str(body_str)?;
     tracing::info!("Received event: {}", payload);
     let event_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
@@ -135,6 +144,68 @@ pub(crate) async fn function_handler(event: Request) -> Result<Response<Body>, E
     }
 }
 
+/// Handles interactivity payloads. They change findings in Security Hub, so they are only
+/// accepted with a valid signature from `SLACK_SIGNING_SECRET`.
+async fn interactivity_handler(event: &Request, body_str: &str, payload: Value) -> Result<Response<Body>, Error> {
+    let signing_secret = match std::env::var("SLACK_SIGNING_SECRET") {
+        Ok(secret) => secret,
+        Err(_) => {
+            tracing::error!("SLACK_SIGNING_SECRET is not set, rejecting interactivity request");
+            return Ok(Response::builder().status(401).body("".into())?);
+        }
+    };
+    let header = |name: &str| event.headers().get(name).and_then(|v| v.to_str().ok()).unwrap_or("");
+    let now = chrono::Utc::now().timestamp();
+    if !verify_slack_signature(
+        &signing_secret,
+        header("x-slack-request-timestamp"),
+        body_str,
+        header("x-slack-signature"),
+        now,
+    ) {
+        tracing::error!("Rejecting interactivity request with an invalid signature");
+        return Ok(Response::builder().status(401).body("".into())?);
+    }
+
+    let payload_type = payload.get("type").and_then(|v| v.as_str()).unwrap_or("");
+    if payload_type != "block_actions" {
+        tracing::info!("Ignoring interactivity payload: {}", payload_type);
+        return Ok(Response::builder().status(200).body("".into())?);
+    }
+
+    let payload: BlockActionsPayload = serde_json::from_value(payload)?;
+    let config = aws_config::defaults(BehaviorVersion::latest())
+        .load()
+        .await;
+
+    // Slack gives up on interactions not acknowledged within 3 seconds, so the clicks are
+    // handled by an asynchronous invocation of this function once Slack has its answer
+    if !event.headers().contains_key(DEFERRED_HEADER) {
+        let function = event.lambda_context().invoked_function_arn;
+        let deferred = deferred_request(
+            body_str,
+            header("x-slack-request-timestamp"),
+            header("x-slack-signature"),
+            chrono::Utc::now().timestamp_millis(),
+        );
+        match invoke_deferred(&aws_sdk_lambda::Client::new(&config), &function, &deferred).await {
+            Ok(()) => return Ok(Response::builder().status(200).body("".into())?),
+            Err(e) => tracing::error!("Failed to defer interaction to {}, handling it now: {}", function, e),
+        }
+    }
+
+    let secrets_client = SMClient::new(&config);
+    let secret_name = "slack-token";
+    let token = get_secret(&secrets_client, secret_name).await
+        .map_err(|e| format!("Failed to retrieve secret '{}': {}", secret_name, e))?;
+
+    let security_hub = aws_sdk_securityhub::Client::new(&config);
+    handle_block_actions(&payload, &security_hub, &token).await?;
+
+    // Slack only needs an empty 200 to acknowledge the interaction
+    Ok(Response::builder().status(200).body("".into())?)
+}
+
 async fn get_secret(
     client: &SMClient,
     secret_name: &str,
@@ -0,0 +1,412 @@
+use aws_sdk_lambda::primitives::Blob;
+use aws_sdk_lambda::types::InvocationType;
+use aws_sdk_lambda::Client as LambdaClient;
+use aws_sdk_securityhub::types::WorkflowStatus;
+use aws_sdk_securityhub::Client as SecurityHubClient;
+use chrono::DateTime;
+use lambda_http::{tracing, Error};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use crate::securityhub::{set_workflow_status, FindingIdentifier};
+use crate::slack_client::{post_response_url, update_slack_message_with_blocks};
+
+/// Block and action IDs of the workflow buttons rendered by `slack-security-hub`.
+const WORKFLOW_BLOCK_ID: &str = "finding_workflow";
+const ACKNOWLEDGE_ACTION_ID: &str = "acknowledge_finding";
+const SUPPRESS_ACTION_ID: &str = "suppress_finding";
+const RESOLVE_ACTION_ID: &str = "resolve_finding";
+
+/// Context block listing who changed the workflow status of the finding, and when.
+const WORKFLOW_LOG_BLOCK_ID: &str = "finding_workflow_log";
+/// Slack context blocks hold at most 10 elements.
+const MAX_LOG_ENTRIES: usize = 10;
+
+/// Header marking the copy of an interactivity request the function sends to itself, so
+/// the clicks are handled after Slack got its acknowledgement.
+pub const DEFERRED_HEADER: &str = "x-slack-deferred";
+
+/// `block_actions` interactivity payload sent when a user clicks a button.
+#[derive(Deserialize, Debug)]
+pub struct BlockActionsPayload {
+    pub user: SlackUser,
+    #[serde(default)]
+    pub channel: Option<SlackChannel>,
+    #[serde(default)]
+    pub container: Option<Container>,
+    #[serde(default)]
+    pub message: Option<SlackMessage>,
+    #[serde(default)]
+    pub response_url: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<BlockAction>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SlackUser {
+    pub id: String,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SlackChannel {
+    pub id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Container {
+    #[serde(default)]
+    pub message_ts: Option<String>,
+    #[serde(default)]
+    pub channel_id: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SlackMessage {
+    #[serde(default)]
+    pub blocks: Vec<Value>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BlockAction {
+    pub action_id: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Unix timestamp with microseconds, e.g. `1772460312.000100`.
+    #[serde(default)]
+    pub action_ts: Option<String>,
+}
+
+/// Workflow button clicked on a finding message.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WorkflowAction {
+    Acknowledge,
+    Suppress,
+    Resolve,
+}
+
+impl WorkflowAction {
+    pub fn from_action_id(action_id: &str) -> Option<Self> {
+        match action_id {
+            ACKNOWLEDGE_ACTION_ID => Some(Self::Acknowledge),
+            SUPPRESS_ACTION_ID => Some(Self::Suppress),
+            RESOLVE_ACTION_ID => Some(Self::Resolve),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> WorkflowStatus {
+        match self {
+            Self::Acknowledge => WorkflowStatus::Notified,
+            Self::Suppress => WorkflowStatus::Suppressed,
+            Self::Resolve => WorkflowStatus::Resolved,
+        }
+    }
+
+    /// OCSF `status_id` of the finding after the action: In Progress, Suppressed or Resolved.
+    pub fn status_id(&self) -> i32 {
+        match self {
+            Self::Acknowledge => 2,
+            Self::Suppress => 3,
+            Self::Resolve => 4,
+        }
+    }
+
+    fn verb(&self) -> &'static str {
+        match self {
+            Self::Acknowledge => "Acknowledged",
+            Self::Suppress => "Suppressed",
+            Self::Resolve => "Resolved",
+        }
+    }
+
+    fn emoji(&self) -> &'static str {
+        match self {
+            Self::Acknowledge => ":eyes:",
+            Self::Suppress => ":white_circle:",
+            Self::Resolve => ":large_green_circle:",
+        }
+    }
+}
+
+/// Returns the JSON `payload` field of a form-encoded interactivity request, `None` for
+/// other requests.
+pub fn form_payload(body: &str) -> Option<Value> {
+    let fields: Vec<(String, String)> = serde_urlencoded::from_str(body).ok()?;
+    let (_, payload) = fields.into_iter().find(|(name, _)| name == "payload")?;
+    serde_json::from_str(&payload).ok()
+}
+
+/// Function URL event carrying the signed interactivity request, with `DEFERRED_HEADER`
+/// set. The copy is still verified against the Slack signature when it is handled.
+pub fn deferred_request(body: &str, timestamp: &str, signature: &str, now_millis: i64) -> Value {
+    json!({
+        "version": "2.0",
+        "routeKey": "$default",
+        "rawPath": "/",
+        "rawQueryString": "",
+        "headers": {
+            "content-type": "application/x-www-form-urlencoded",
+            "x-slack-request-timestamp": timestamp,
+            "x-slack-signature": signature,
+            DEFERRED_HEADER: "true"
+        },
+        "requestContext": {
+            "routeKey": "$default",
+            "stage": "$default",
+            "timeEpoch": now_millis,
+            "http": {
+                "method": "POST",
+                "path": "/",
+                "protocol": "HTTP/1.1",
+                "sourceIp": "127.0.0.1",
+                "userAgent": "slack-event"
+            }
+        },
+        "body": body,
+        "isBase64Encoded": false
+    })
+}
+
+/// Invokes `function` asynchronously with `event`, returning once Lambda queued it.
+pub async fn invoke_deferred(client: &LambdaClient, function: &str, event: &Value) -> Result<(), Error> {
+    client
+        .invoke()
+        .function_name(function)
+        .invocation_type(InvocationType::Event)
+        .payload(Blob::new(serde_json::to_vec(event)?))
+        .send()
+        .await?;
+    Ok(())
+}
+
+/// Updates the Security Hub workflow status of the finding behind each clicked workflow
+/// button, then updates the message in place to show who acted and when. Failures are
+/// reported to the clicking user with an ephemeral message.
+pub async fn handle_block_actions(
+    payload: &BlockActionsPayload,
+    security_hub: &SecurityHubClient,
+    token: &str,
+) -> Result<(), Error> {
+    let user_name = payload.user.name.as_deref()
+        .or(payload.user.username.as_deref())
+        .unwrap_or(&payload.user.id);
+
+    for action in &payload.actions {
+        let Some(workflow_action) = WorkflowAction::from_action_id(&action.action_id) else {
+            tracing::info!("Ignoring action: {}", action.action_id);
+            continue;
+        };
+        let finding: FindingIdentifier = match action.value.as_deref().map(serde_json::from_str) {
+            Some(Ok(finding)) => finding,
+            _ => {
+                tracing::error!("Action {} has no finding identifier", action.action_id);
+                continue;
+            }
+        };
+
+        tracing::info!("{} finding {} by {}", workflow_action.verb(), finding.id(), user_name);
+        // slack-security-hub recognizes this note on the status change event and leaves the
+        // message, already updated below, as it is
+        let note = format!("{} by {} in Slack", workflow_action.verb(), user_name);
+        let status_id = workflow_action.status_id();
+        if let Err(e) = set_workflow_status(security_hub, &finding, workflow_action.status(), status_id, &note, user_name).await {
+            let err_msg = format!("Failed to update finding {}: {}", finding.id(), e);
+            tracing::error!("{}", err_msg);
+            if let Some(response_url) = &payload.response_url {
+                let message = json!({
+                    "response_type": "ephemeral",
+                    "replace_original": false,
+                    "text": format!(":warning: {}", err_msg)
+                });
+                if let Err(e) = post_response_url(response_url, &message).await {
+                    tracing::error!("Failed to report the error to Slack: {}", e);
+                }
+            }
+            continue;
+        }
+
+        let channel = payload.channel.as_ref().map(|c| c.id.as_str())
+            .or(payload.container.as_ref().and_then(|c| c.channel_id.as_deref()));
+        let ts = payload.container.as_ref().and_then(|c| c.message_ts.as_deref());
+        let (Some(channel), Some(ts), Some(message)) = (channel, ts, &payload.message) else {
+            tracing::warn!("Action {} has no message to update", action.action_id);
+            continue;
+        };
+
+        let acted_at = action.action_ts.as_deref()
+            .and_then(|ts| ts.parse::<f64>().ok())
+            .map(|ts| ts as i64)
+            .unwrap_or_else(|| chrono::Utc::now().timestamp());
+        let blocks = updated_blocks(&message.blocks, workflow_action, &payload.user.id, acted_at);
+        if let Err(e) = update_slack_message_with_blocks(token, channel, ts, json!(blocks)).await {
+            tracing::error!("Failed to update message {} in {}: {}", ts, channel, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Blocks of the finding message after `action`: the clicked buttons are removed (only
+/// Acknowledge for acknowledged findings) and the action is added to the workflow log.
+pub fn updated_blocks(blocks: &[Value], action: WorkflowAction, user_id: &str, acted_at: i64) -> Vec<Value> {
+    let mut updated = Vec::with_capacity(blocks.len() + 1);
+    let mut log_position = None;
+
+    for block in blocks {
+        if block["block_id"] != WORKFLOW_BLOCK_ID {
+            updated.push(block.clone());
+            continue;
+        }
+        log_position = Some(updated.len());
+        if action == WorkflowAction::Acknowledge {
+            let mut block = block.clone();
+            if let Some(elements) = block["elements"].as_array_mut() {
+                elements.retain(|e| e["action_id"] != ACKNOWLEDGE_ACTION_ID);
+            }
+            updated.push(block);
+            log_position = Some(updated.len());
+        }
+    }
+
+    let entry = json!({
+        "type": "mrkdwn",
+        "text": format!("{} *{}* by <@{}> {}", action.emoji(), action.verb(), user_id, slack_date(acted_at))
+    });
+
+    if let Some(log) = updated.iter_mut().find(|b| b["block_id"] == WORKFLOW_LOG_BLOCK_ID) {
+        if let Some(elements) = log["elements"].as_array_mut() {
+            elements.push(entry);
+            if elements.len() > MAX_LOG_ENTRIES {
+                elements.remove(0);
+            }
+        }
+        return updated;
+    }
+
+    // Messages without workflow buttons get the log above their closing divider
+    let position = log_position
+        .or_else(|| updated.iter().rposition(|b| b["type"] == "divider"))
+        .unwrap_or(updated.len());
+    updated.insert(position, json!({
+        "type": "context",
+        "block_id": WORKFLOW_LOG_BLOCK_ID,
+        "elements": [entry]
+    }));
+
+    updated
+}
+
+/// Slack date formatting, shown in the reader's time zone with a UTC fallback.
+fn slack_date(timestamp: i64) -> String {
+    let fallback = DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_else(|| timestamp.to_string());
+    format!("<!date^{}^{{date_short_pretty}} at {{time}}|{}>", timestamp, fallback)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finding_message() -> Vec<Value> {
+        let value = r#"{"metadata_uid":"arn:aws:guardduty:us-east-1:111111111111:detector/1/finding/2"}"#;
+        vec![
+            json!({ "type": "header", "text": { "type": "plain_text", "text": "Finding" } }),
+            json!({ "type": "actions", "elements": [{ "type": "button", "action_id": "open_security_hub" }] }),
+            json!({ "type": "actions", "block_id": WORKFLOW_BLOCK_ID, "elements": [
+                { "type": "button", "action_id": ACKNOWLEDGE_ACTION_ID, "value": value },
+                { "type": "button", "action_id": SUPPRESS_ACTION_ID, "value": value },
+                { "type": "button", "action_id": RESOLVE_ACTION_ID, "value": value }
+            ] }),
+            json!({ "type": "divider" }),
+        ]
+    }
+
+    #[test]
+    fn test_form_payload() {
+        let body = "payload=%7B%22type%22%3A%22block_actions%22%7D";
+        assert_eq!(form_payload(body).unwrap()["type"], "block_actions");
+        assert!(form_payload("token=abc&command=%2Fsecurity").is_none());
+        assert!(form_payload(r#"{"type":"event_callback"}"#).is_none());
+    }
+
+    #[test]
+    fn test_block_actions_payload() {
+        let payload: BlockActionsPayload = serde_json::from_value(json!({
+            "type": "block_actions",
+            "user": { "id": "U0456DEF", "username": "alice", "name": "alice" },
+            "container": { "type": "message", "message_ts": "1772460312.000100", "channel_id": "C123" },
+            "channel": { "id": "C123", "name": "aws-security" },
+            "message": { "blocks": finding_message() },
+            "response_url": "https://hooks.slack.com/actions/T1/2/3",
+            "actions": [{
+                "action_id": RESOLVE_ACTION_ID,
+                "block_id": WORKFLOW_BLOCK_ID,
+                "value": finding_message()[2]["elements"][2]["value"],
+                "action_ts": "1772460400.123456"
+            }]
+        })).unwrap();
+        let action = &payload.actions[0];
+        let finding: FindingIdentifier = serde_json::from_str(action.value.as_deref().unwrap()).unwrap();
+
+        assert_eq!(WorkflowAction::from_action_id(&action.action_id), Some(WorkflowAction::Resolve));
+        assert_eq!(WorkflowAction::Resolve.status(), WorkflowStatus::Resolved);
+        assert_eq!(finding.id(), "arn:aws:guardduty:us-east-1:111111111111:detector/1/finding/2");
+        assert!(matches!(finding, FindingIdentifier::Ocsf { .. }));
+        assert!(WorkflowAction::from_action_id("open_security_hub").is_none());
+    }
+
+    #[test]
+    fn test_asff_finding_identifier() {
+        let finding: FindingIdentifier = serde_json::from_str(
+            r#"{"id":"arn:aws:securityhub:us-east-1:111111111111:subscription/aws-foundational/v/1.0.0/S3.8/finding/1","product_arn":"arn:aws:securityhub:us-east-1::product/aws/securityhub"}"#
+        ).unwrap();
+
+        assert!(matches!(finding, FindingIdentifier::Asff { ref product_arn, .. } if product_arn.ends_with("product/aws/securityhub")));
+    }
+
+    #[test]
+    fn test_deferred_request_is_a_signed_http_request() {
+        let body = "payload=%7B%22type%22%3A%22block_actions%22%7D";
+        let event = deferred_request(body, "1772460312", "v0=abc", 1772460312000);
+        let request: lambda_http::request::LambdaRequest = serde_json::from_value(event).unwrap();
+        let request = lambda_http::Request::from(request);
+
+        assert_eq!(request.method(), "POST");
+        assert_eq!(request.headers()[DEFERRED_HEADER], "true");
+        assert_eq!(request.headers()["x-slack-request-timestamp"], "1772460312");
+        assert_eq!(request.headers()["x-slack-signature"], "v0=abc");
+        match request.body() {
+            lambda_http::Body::Text(text) => assert_eq!(text, body),
+            other => panic!("unexpected body: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_acknowledge_keeps_other_buttons() {
+        let blocks = updated_blocks(&finding_message(), WorkflowAction::Acknowledge, "U0456DEF", 1772460400);
+
+        assert_eq!(blocks[2]["elements"].as_array().unwrap().len(), 2);
+        assert_eq!(blocks[3]["block_id"], WORKFLOW_LOG_BLOCK_ID);
+        assert_eq!(
+            blocks[3]["elements"][0]["text"],
+            ":eyes: *Acknowledged* by <@U0456DEF> <!date^1772460400^{date_short_pretty} at {time}|2026-03-02 14:06 UTC>"
+        );
+        assert_eq!(blocks[4]["type"], "divider");
+    }
+
+    #[test]
+    fn test_resolve_removes_buttons_and_extends_log() {
+        let acknowledged = updated_blocks(&finding_message(), WorkflowAction::Acknowledge, "U0456DEF", 1772460400);
+        let blocks = updated_blocks(&acknowledged, WorkflowAction::Resolve, "U0789GHI", 1772461000);
+
+        assert!(!blocks.iter().any(|b| b["block_id"] == WORKFLOW_BLOCK_ID));
+        let log = blocks.iter().find(|b| b["block_id"] == WORKFLOW_LOG_BLOCK_ID).unwrap();
+        assert_eq!(log["elements"].as_array().unwrap().len(), 2);
+        assert!(log["elements"][1]["text"].as_str().unwrap().starts_with(":large_green_circle: *Resolved* by <@U0789GHI>"));
+        assert_eq!(blocks.last().unwrap()["type"], "divider");
+    }
+}
@@ -1,6 +1,9 @@
 use lambda_http::{run, service_fn, tracing, Error};
 mod slack_client;
 mod http_handler;
+mod interactivity;
+mod securityhub;
+mod signature;
 use http_handler::function_handler;
 
 #[tokio::main]
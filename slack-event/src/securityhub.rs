@@ -0,0 +1,82 @@
+use aws_sdk_securityhub::types::{AwsSecurityFindingIdentifier, NoteUpdate, WorkflowStatus, WorkflowUpdate};
+use aws_sdk_securityhub::Client;
+use lambda_http::Error;
+use serde::Deserialize;
+
+/// Identifies a finding in Security Hub updates, as embedded by `slack-security-hub` in
+/// the `value` of the workflow buttons.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FindingIdentifier {
+    /// OCSF finding, updated by `metadata.uid` with BatchUpdateFindingsV2.
+    Ocsf { metadata_uid: String },
+    /// Finding from a legacy ASFF event, updated by `Id` and `ProductArn` with
+    /// BatchUpdateFindings.
+    Asff { id: String, product_arn: String },
+}
+
+impl FindingIdentifier {
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Ocsf { metadata_uid } => metadata_uid,
+            Self::Asff { id, .. } => id,
+        }
+    }
+}
+
+/// Notes longer than this are rejected by Security Hub.
+const MAX_NOTE_LENGTH: usize = 512;
+
+/// Sets the workflow status of the finding and records `note` on it, attributed to
+/// `updated_by`. OCSF findings take the status as `status_id`, and their comment has no
+/// author.
+pub async fn set_workflow_status(
+    client: &Client,
+    finding: &FindingIdentifier,
+    status: WorkflowStatus,
+    status_id: i32,
+    note: &str,
+    updated_by: &str,
+) -> Result<(), Error> {
+    let note: String = note.chars().take(MAX_NOTE_LENGTH).collect();
+
+    let error_message = match finding {
+        FindingIdentifier::Ocsf { metadata_uid } => {
+            let output = client
+                .batch_update_findings_v2()
+                .metadata_uids(metadata_uid)
+                .status_id(status_id)
+                .comment(note)
+                .send()
+                .await?;
+            output.unprocessed_findings().first()
+                .map(|unprocessed| unprocessed.error_message().unwrap_or("unknown error").to_string())
+        }
+        FindingIdentifier::Asff { id, product_arn } => {
+            let identifier = AwsSecurityFindingIdentifier::builder()
+                .id(id)
+                .product_arn(product_arn)
+                .build();
+            let note = NoteUpdate::builder()
+                .text(note)
+                .updated_by(updated_by.chars().take(MAX_NOTE_LENGTH).collect::<String>())
+                .build();
+
+            let output = client
+                .batch_update_findings()
+                .finding_identifiers(identifier)
+                .workflow(WorkflowUpdate::builder().status(status).build())
+                .note(note)
+                .send()
+                .await?;
+            output.unprocessed_findings().first()
+                .map(|unprocessed| unprocessed.error_message().unwrap_or("unknown error").to_string())
+        }
+    };
+
+    if let Some(error_message) = error_message {
+        return Err(format!("Security Hub did not update finding {}: {}", finding.id(), error_message).into());
+    }
+
+    Ok(())
+}
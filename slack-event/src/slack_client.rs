@@ -10,7 +10,7 @@ pub async fn post_slack_message(
 ) -> Result<(), Box<dyn Error>> {
 
     // Build the blocks for the Slack message
-    let mut blocks = vec![
+    let blocks = vec![
         json!({
 			"type": "section",
 			"text": {
@@ -67,4 +67,59 @@ pub async fn post_slack_message_with_blocks(
     } else {
         Err(format!("HTTP error: {}", response.status()).into())
     }
-}
\ No newline at end of file
+}
+
+/// Replaces the blocks of a posted message with `chat.update`.
+pub async fn update_slack_message_with_blocks(
+    token: &str,
+    channel: &str,
+    ts: &str,
+    all_blocks: Value,
+) -> Result<(), Box<dyn Error>> {
+    let client = Client::new();
+    let url = "https://slack.com/api/chat.update";
+
+    let payload = serde_json::json!({
+        "channel": channel,
+        "ts": ts,
+        "blocks": all_blocks
+    });
+
+    let response = client
+        .post(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json; charset=utf-8")
+        .json(&payload)
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        let response_body: Value = response.json().await?;
+        if response_body["ok"].as_bool().unwrap_or(false) {
+            Ok(())
+        } else {
+            let error = response_body["error"]
+                .as_str()
+                .unwrap_or("Unknown error");
+            Err(format!("Slack API error: {}", error).into())
+        }
+    } else {
+        Err(format!("HTTP error: {}", response.status()).into())
+    }
+}
+
+/// Sends a message to the `response_url` of an interaction, e.g. an ephemeral error for
+/// the user who clicked a button.
+pub async fn post_response_url(response_url: &str, message: &Value) -> Result<(), Box<dyn Error>> {
+    let response = Client::new()
+        .post(response_url)
+        .json(message)
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP error: {}", response.status()).into())
+    }
+}
@@ -0,0 +1,351 @@
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_organizations::types::ParentType;
+use lambda_runtime::{tracing, Error};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::time::Instant;
+
+const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// Organizations lookups cached for the lifetime of the Lambda execution environment,
+/// so warm invocations reuse them. Created by the first enricher with the configured TTL.
+static SHARED_CACHE: OnceLock<Arc<AccountCache>> = OnceLock::new();
+
+/// Name, organizational unit path and tags of an AWS account.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccountInfo {
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Names of the organizational units from the root down to the account.
+    #[serde(default)]
+    pub ou_path: Vec<String>,
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Looks up account details by account ID.
+#[async_trait]
+pub trait AccountDirectory: Send + Sync {
+    fn name(&self) -> &'static str;
+    async fn lookup(&self, account_id: &str) -> Result<Option<AccountInfo>, Error>;
+}
+
+/// Reads account details from AWS Organizations. Requires running in the management
+/// account or a delegated administrator account.
+pub struct OrganizationsDirectory {
+    client: aws_sdk_organizations::Client,
+}
+
+impl OrganizationsDirectory {
+    pub fn new(config: &SdkConfig) -> Self {
+        Self {
+            client: aws_sdk_organizations::Client::new(config),
+        }
+    }
+
+    async fn ou_path(&self, account_id: &str) -> Result<Vec<String>, Error> {
+        let mut path = Vec::new();
+        let mut child = account_id.to_string();
+
+        // Walk up the parents until the root is reached
+        loop {
+            let parents = self.client.list_parents().child_id(&child).send().await?;
+            let Some(parent) = parents.parents().first() else {
+                break;
+            };
+            let Some(parent_id) = parent.id() else {
+                break;
+            };
+            if parent.r#type() != Some(&ParentType::OrganizationalUnit) {
+                break;
+            }
+
+            let ou = self.client
+                .describe_organizational_unit()
+                .organizational_unit_id(parent_id)
+                .send()
+                .await?;
+            if let Some(name) = ou.organizational_unit().and_then(|ou| ou.name()) {
+                path.insert(0, name.to_string());
+            }
+            child = parent_id.to_string();
+        }
+
+        Ok(path)
+    }
+}
+
+#[async_trait]
+impl AccountDirectory for OrganizationsDirectory {
+    fn name(&self) -> &'static str {
+        "organizations"
+    }
+
+    async fn lookup(&self, account_id: &str) -> Result<Option<AccountInfo>, Error> {
+        let account = self.client.describe_account().account_id(account_id).send().await?;
+        let name = account.account().and_then(|a| a.name()).map(str::to_string);
+
+        let mut tags = BTreeMap::new();
+        let mut pages = self.client
+            .list_tags_for_resource()
+            .resource_id(account_id)
+            .into_paginator()
+            .items()
+            .send();
+        while let Some(tag) = pages.next().await {
+            let tag = tag?;
+            tags.insert(tag.key().to_string(), tag.value().to_string());
+        }
+
+        Ok(Some(AccountInfo {
+            name,
+            ou_path: self.ou_path(account_id).await?,
+            tags,
+        }))
+    }
+}
+
+/// Account details from a static JSON mapping of account ID to `AccountInfo`.
+#[derive(Debug, Clone, Default)]
+pub struct StaticDirectory {
+    accounts: HashMap<String, AccountInfo>,
+}
+
+impl StaticDirectory {
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let accounts = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse account mapping: {}", e))?;
+        Ok(Self { accounts })
+    }
+}
+
+#[async_trait]
+impl AccountDirectory for StaticDirectory {
+    fn name(&self) -> &'static str {
+        "static"
+    }
+
+    async fn lookup(&self, account_id: &str) -> Result<Option<AccountInfo>, Error> {
+        Ok(self.accounts.get(account_id).cloned())
+    }
+}
+
+/// TTL cache of account lookups, including accounts that were not found.
+pub struct AccountCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, Option<AccountInfo>)>>,
+}
+
+impl AccountCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, account_id: &str) -> Option<Option<AccountInfo>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(account_id)
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, info)| info.clone())
+    }
+
+    fn insert(&self, account_id: &str, info: Option<AccountInfo>) {
+        self.entries.lock().unwrap().insert(account_id.to_string(), (Instant::now(), info));
+    }
+}
+
+/// Resolves account details through the configured directories in order, falling back
+/// to the next directory when one fails or does not know the account.
+pub struct AccountEnricher {
+    directories: Vec<Box<dyn AccountDirectory>>,
+    cache: Arc<AccountCache>,
+}
+
+impl AccountEnricher {
+    pub fn new(directories: Vec<Box<dyn AccountDirectory>>, cache: Arc<AccountCache>) -> Self {
+        Self { directories, cache }
+    }
+
+    /// Enables Organizations lookups with `ORGANIZATIONS_ENRICHMENT=true` and the static
+    /// mapping with `ACCOUNT_MAPPING` (inline JSON) or `ACCOUNT_MAPPING_FILE`.
+    /// `ACCOUNT_CACHE_TTL_SECONDS` sets how long lookups are cached (default one hour).
+    /// Returns `None` when no directory is configured.
+    pub fn from_env(config: &SdkConfig) -> Result<Option<Self>, Error> {
+        let mut directories: Vec<Box<dyn AccountDirectory>> = Vec::new();
+
+        if std::env::var("ORGANIZATIONS_ENRICHMENT").is_ok_and(|v| v.eq_ignore_ascii_case("true")) {
+            directories.push(Box::new(OrganizationsDirectory::new(config)));
+        }
+
+        if let Ok(json) = std::env::var("ACCOUNT_MAPPING") {
+            directories.push(Box::new(StaticDirectory::from_json(&json)?));
+        } else if let Ok(path) = std::env::var("ACCOUNT_MAPPING_FILE") {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read account mapping file '{}': {}", path, e))?;
+            directories.push(Box::new(StaticDirectory::from_json(&contents)?));
+        }
+
+        if directories.is_empty() {
+            return Ok(None);
+        }
+
+        let ttl = match std::env::var("ACCOUNT_CACHE_TTL_SECONDS") {
+            Ok(ttl) => Duration::from_secs(ttl.trim().parse()
+                .map_err(|e| format!("Invalid ACCOUNT_CACHE_TTL_SECONDS '{}': {}", ttl, e))?),
+            Err(_) => DEFAULT_CACHE_TTL,
+        };
+        let cache = SHARED_CACHE.get_or_init(|| Arc::new(AccountCache::new(ttl))).clone();
+
+        Ok(Some(Self::new(directories, cache)))
+    }
+
+    pub async fn lookup(&self, account_id: &str) -> Option<AccountInfo> {
+        if let Some(info) = self.cache.get(account_id) {
+            return info;
+        }
+
+        let mut info = None;
+        let mut failed = false;
+        for directory in &self.directories {
+            match directory.lookup(account_id).await {
+                Ok(Some(found)) => {
+                    info = Some(found);
+                    break;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::warn!("Failed to look up account {} in {}: {}", account_id, directory.name(), e);
+                    failed = true;
+                }
+            }
+        }
+
+        // A failed directory may know the account on the next lookup (e.g. after a throttling
+        // error), so only complete answers are cached
+        if !failed {
+            self.cache.insert(account_id, info.clone());
+        }
+        info
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const MAPPING: &str = r#"{
+        "111111111111": {
+            "name": "payments-prod",
+            "ou_path": ["Workloads", "Prod"],
+            "tags": {"environment": "prod", "owner": "team-payments"}
+        }
+    }"#;
+
+    /// Directory that fails or answers with a fixed name and counts its lookups.
+    struct CountingDirectory {
+        calls: Arc<AtomicUsize>,
+        fail: bool,
+    }
+
+    #[async_trait]
+    impl AccountDirectory for CountingDirectory {
+        fn name(&self) -> &'static str {
+            "counting"
+        }
+
+        async fn lookup(&self, _account_id: &str) -> Result<Option<AccountInfo>, Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.fail {
+                return Err("AccessDeniedException".into());
+            }
+            Ok(Some(AccountInfo { name: Some("from-organizations".to_string()), ..Default::default() }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_static_mapping() {
+        let directory = StaticDirectory::from_json(MAPPING).unwrap();
+        let info = directory.lookup("111111111111").await.unwrap().unwrap();
+
+        assert_eq!(info.name.as_deref(), Some("payments-prod"));
+        assert_eq!(info.ou_path, vec!["Workloads", "Prod"]);
+        assert_eq!(info.tags["owner"], "team-payments");
+        assert_eq!(directory.lookup("999999999999").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_static_mapping_on_error() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let enricher = AccountEnricher::new(
+            vec![
+                Box::new(CountingDirectory { calls: calls.clone(), fail: true }),
+                Box::new(StaticDirectory::from_json(MAPPING).unwrap()),
+            ],
+            Arc::new(AccountCache::new(DEFAULT_CACHE_TTL)),
+        );
+
+        let info = enricher.lookup("111111111111").await.unwrap();
+        assert_eq!(info.name.as_deref(), Some("payments-prod"));
+        assert_eq!(enricher.lookup("999999999999").await, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_lookups_are_cached_until_ttl() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let cache = Arc::new(AccountCache::new(Duration::from_secs(60)));
+        let directory = || -> Vec<Box<dyn AccountDirectory>> {
+            vec![Box::new(CountingDirectory { calls: calls.clone(), fail: false })]
+        };
+
+        // A second enricher sharing the cache stands in for a warm invocation
+        AccountEnricher::new(directory(), cache.clone()).lookup("111111111111").await;
+        AccountEnricher::new(directory(), cache.clone()).lookup("111111111111").await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let info = AccountEnricher::new(directory(), cache).lookup("111111111111").await;
+        assert_eq!(info.unwrap().name.as_deref(), Some("from-organizations"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_failed_lookups_are_not_cached() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let enricher = AccountEnricher::new(
+            vec![
+                Box::new(CountingDirectory { calls: calls.clone(), fail: true }),
+                Box::new(StaticDirectory::from_json(MAPPING).unwrap()),
+            ],
+            Arc::new(AccountCache::new(DEFAULT_CACHE_TTL)),
+        );
+
+        assert_eq!(enricher.lookup("111111111111").await.unwrap().name.as_deref(), Some("payments-prod"));
+        assert_eq!(enricher.lookup("111111111111").await.unwrap().name.as_deref(), Some("payments-prod"));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_enrichers_from_env_share_the_cache() {
+        // SAFETY: no other test reads these variables
+        unsafe {
+            std::env::set_var("ACCOUNT_MAPPING", MAPPING);
+            std::env::set_var("ACCOUNT_CACHE_TTL_SECONDS", "600");
+        }
+        let config = SdkConfig::builder().build();
+
+        // The second enricher stands in for a warm invocation
+        let first = AccountEnricher::from_env(&config).unwrap().unwrap();
+        first.lookup("111111111111").await;
+        let second = AccountEnricher::from_env(&config).unwrap().unwrap();
+
+        assert!(Arc::ptr_eq(&first.cache, &second.cache));
+        assert_eq!(second.cache.get("111111111111").unwrap().unwrap().name.as_deref(), Some("payments-prod"));
+    }
+}
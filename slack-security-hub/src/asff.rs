@@ -0,0 +1,332 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::severity::Severity;
+use crate::struct_event::{
+    Account, Cloud, Compliance, Finding, FindingInfo, Metadata, MetadataProduct, Remediation, Resource, Tag,
+};
+
+/// Detail of the legacy "Security Hub Findings - Imported" event (AWS Security Finding Format).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AsffDetail {
+    pub findings: Option<Vec<AsffFinding>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffFinding {
+    pub schema_version: Option<String>,
+    pub id: Option<String>,
+    pub product_arn: Option<String>,
+    pub product_name: Option<String>,
+    pub company_name: Option<String>,
+    pub region: Option<String>,
+    pub generator_id: Option<String>,
+    pub aws_account_id: Option<String>,
+    pub types: Option<Vec<String>>,
+    pub first_observed_at: Option<String>,
+    pub last_observed_at: Option<String>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub severity: Option<AsffSeverity>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub remediation: Option<AsffRemediation>,
+    pub resources: Option<Vec<AsffResource>>,
+    pub workflow: Option<AsffWorkflow>,
+    pub note: Option<AsffNote>,
+    pub record_state: Option<String>,
+    pub compliance: Option<AsffCompliance>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffSeverity {
+    pub label: Option<String>,
+    pub normalized: Option<i32>,
+    pub original: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffRemediation {
+    pub recommendation: Option<AsffRecommendation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffRecommendation {
+    pub text: Option<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffResource {
+    #[serde(rename = "Type")]
+    pub resource_type: Option<String>,
+    pub id: Option<String>,
+    pub partition: Option<String>,
+    pub region: Option<String>,
+    pub tags: Option<HashMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffCompliance {
+    pub status: Option<String>,
+    pub security_control_id: Option<String>,
+    pub related_requirements: Option<Vec<String>>,
+    pub associated_standards: Option<Vec<AsffStandard>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffStandard {
+    pub standards_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffWorkflow {
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AsffNote {
+    pub text: Option<String>,
+    pub updated_by: Option<String>,
+}
+
+impl AsffSeverity {
+    /// Uses the label when present, otherwise maps the normalized 0-100 score.
+    pub fn to_severity(&self) -> Severity {
+        if let Some(severity) = self.label.as_deref().and_then(|l| l.parse().ok()) {
+            return severity;
+        }
+
+        match self.normalized {
+            Some(0) => Severity::Informational,
+            Some(1..=39) => Severity::Low,
+            Some(40..=69) => Severity::Medium,
+            Some(70..=89) => Severity::High,
+            Some(90..=100) => Severity::Critical,
+            _ => Severity::Unknown,
+        }
+    }
+}
+
+impl AsffFinding {
+    /// Normalizes the ASFF finding into the OCSF `Finding` used by the rest of the pipeline.
+    pub fn to_ocsf(&self) -> Finding {
+        let severity = self.severity.as_ref()
+            .map(AsffSeverity::to_severity)
+            .unwrap_or(Severity::Unknown);
+
+        let status = self.workflow.as_ref()
+            .and_then(|w| w.status.as_deref())
+            .map(title_case);
+
+        let resources: Vec<Resource> = self.resources.as_deref().unwrap_or_default()
+            .iter()
+            .map(|r| Resource {
+                cloud_partition: r.partition.clone(),
+                region: r.region.clone(),
+                resource_type: r.resource_type.clone(),
+                uid: r.id.clone(),
+                tags: r.tags.as_ref().map(|tags| {
+                    let mut tags: Vec<Tag> = tags.iter()
+                        .map(|(name, value)| Tag { name: Some(name.clone()), value: Some(value.clone()) })
+                        .collect();
+                    tags.sort_by(|a, b| a.name.cmp(&b.name));
+                    tags
+                }),
+                ..Default::default()
+            })
+            .collect();
+
+        // Findings from integrations may omit the top-level region
+        let region = self.region.clone()
+            .or_else(|| resources.iter().find_map(|r| r.region.clone()));
+        let partition = resources.iter().find_map(|r| r.cloud_partition.clone());
+
+        let remediation = self.remediation.as_ref()
+            .and_then(|r| r.recommendation.as_ref())
+            .map(|r| Remediation {
+                desc: r.text.clone(),
+                references: r.url.clone().map(|url| vec![url]),
+            });
+
+        let compliance = self.compliance.as_ref().map(|c| Compliance {
+            control: c.security_control_id.clone(),
+            requirements: c.related_requirements.clone(),
+            standards: c.associated_standards.as_ref()
+                .map(|standards| standards.iter().filter_map(|s| s.standards_id.clone()).collect()),
+            status: c.status.clone(),
+            ..Default::default()
+        });
+
+        Finding {
+            cloud: Some(Cloud {
+                account: Some(Account {
+                    uid: self.aws_account_id.clone(),
+                    ..Default::default()
+                }),
+                cloud_partition: partition,
+                provider: Some("AWS".to_string()),
+                region,
+            }),
+            comment: self.note.as_ref().and_then(|n| n.text.clone()),
+            compliance,
+            finding_info: Some(FindingInfo {
+                created_time_dt: self.created_at.clone(),
+                desc: self.description.clone(),
+                first_seen_time_dt: self.first_observed_at.clone(),
+                last_seen_time_dt: self.last_observed_at.clone(),
+                modified_time_dt: self.updated_at.clone(),
+                title: self.title.clone(),
+                types: self.types.clone(),
+                uid: self.id.clone(),
+                ..Default::default()
+            }),
+            metadata: Some(Metadata {
+                product: Some(MetadataProduct {
+                    name: self.product_name.clone(),
+                    uid: self.product_arn.clone(),
+                    vendor_name: self.company_name.clone(),
+                    ..Default::default()
+                }),
+                version: self.schema_version.clone(),
+                ..Default::default()
+            }),
+            remediation,
+            resources: Some(resources),
+            severity: Some(severity.to_string()),
+            status,
+            from_asff: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Converts ASFF enum values such as `NOTIFIED` into the OCSF style `Notified`.
+fn title_case(value: &str) -> String {
+    value
+        .split('_')
+        .map(|word| {
+            let lower = word.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::securityhub::FindingIdentifier;
+    use crate::struct_event::{findings_from_json, parse_findings, FindingSummary};
+    use serde_json::Value;
+
+    fn summaries_from_fixture(fixture: &str) -> Vec<FindingSummary> {
+        let event: Value = serde_json::from_str(fixture).unwrap();
+        parse_findings(event["detail-type"].as_str().unwrap(), event["detail"].clone())
+            .unwrap()
+            .iter()
+            .map(FindingSummary::from_finding)
+            .collect()
+    }
+
+    #[test]
+    fn test_asff_guardduty_summary() {
+        let summaries = summaries_from_fixture(include_str!("../fixtures/asff_guardduty_high.json"));
+        let summary = &summaries[0];
+
+        assert_eq!(summary.uid, "arn:aws:guardduty:us-west-2:333333333333:detector/4cb2a8e2f1d3/finding/46c6a8b5e7f2");
+        assert_eq!(summary.title, "API GetBucketAcl was invoked from a known malicious IP address.");
+        assert_eq!(summary.severity, Severity::High);
+        assert_eq!(summary.status, "New");
+        assert_eq!(summary.account, "333333333333");
+        assert_eq!(summary.region, "us-west-2");
+        assert_eq!(summary.product_name, "GuardDuty");
+        assert_eq!(summary.product_aws, "guardduty");
+        assert_eq!(summary.resource_id, "AWS::IAM::AccessKey:ASIAEXAMPLEKEY");
+        assert_eq!(summary.resource_types, vec!["AwsIamAccessKey"]);
+        assert_eq!(summary.resource_tags, vec![("env".to_string(), "prod".to_string())]);
+        assert_eq!(summary.first_seen, "2026-03-01 08:12:44 UTC");
+        assert!(summary.remediation.starts_with("https://docs.aws.amazon.com/guardduty/"));
+        assert_eq!(summary.security_hub_id, Some(FindingIdentifier::Asff {
+            id: summary.uid.clone(),
+            product_arn: summary.product_arn.clone(),
+        }));
+    }
+
+    #[test]
+    fn test_asff_third_party_uses_normalized_severity() {
+        let summaries = summaries_from_fixture(include_str!("../fixtures/asff_third_party_custom_action.json"));
+        let summary = &summaries[0];
+
+        assert_eq!(summary.severity, Severity::Medium);
+        assert_eq!(summary.status, "Notified");
+        assert_eq!(summary.region, "ap-southeast-2");
+        assert_eq!(summary.remediation, "no_remediation");
+        assert_eq!(summary.resource_types, vec!["AwsEc2Instance", "Other"]);
+    }
+
+    #[test]
+    fn test_ocsf_and_asff_share_the_pipeline() {
+        let ocsf = summaries_from_fixture(include_str!("../fixtures/guardduty_prod_critical.json"));
+        assert_eq!(ocsf[0].severity, Severity::Critical);
+        assert_eq!(ocsf[0].product_aws, "guardduty");
+    }
+
+    #[test]
+    fn test_title_case() {
+        assert_eq!(title_case("NOTIFIED"), "Notified");
+        assert_eq!(title_case("IN_PROGRESS"), "In Progress");
+    }
+
+    #[test]
+    fn test_findings_from_event_or_bare_finding() {
+        let ocsf: Value = serde_json::from_str(include_str!("../fixtures/guardduty_prod_critical.json")).unwrap();
+        let asff: Value = serde_json::from_str(include_str!("../fixtures/asff_guardduty_high.json")).unwrap();
+
+        let from_event = findings_from_json(ocsf.clone()).unwrap();
+        let bare_ocsf = findings_from_json(ocsf["detail"]["findings"][0].clone()).unwrap();
+        assert_eq!(
+            FindingSummary::from_finding(&bare_ocsf[0]).uid,
+            FindingSummary::from_finding(&from_event[0]).uid
+        );
+
+        let bare_asff = findings_from_json(asff["detail"]["findings"][0].clone()).unwrap();
+        assert_eq!(FindingSummary::from_finding(&bare_asff[0]).account, "333333333333");
+    }
+
+    #[test]
+    fn test_asff_compliance_is_normalized() {
+        let finding: AsffFinding = serde_json::from_value(serde_json::json!({
+            "Id": "arn:aws:securityhub:us-east-1:111111111111:security-control/S3.8/finding/1a2b",
+            "ProductArn": "arn:aws:securityhub:us-east-1::product/aws/securityhub",
+            "ProductName": "Security Hub",
+            "AwsAccountId": "111111111111",
+            "Title": "S3 general purpose buckets should block public access",
+            "Compliance": {
+                "Status": "FAILED",
+                "SecurityControlId": "S3.8",
+                "RelatedRequirements": ["CIS AWS Foundations Benchmark v1.4.0/2.1.5"],
+                "AssociatedStandards": [{"StandardsId": "standards/cis-aws-foundations-benchmark/v/1.4.0"}]
+            }
+        })).unwrap();
+        let summary = FindingSummary::from_finding(&finding.to_ocsf());
+        let control = summary.control.unwrap();
+
+        assert_eq!(summary.category, crate::compliance::FindingCategory::Compliance);
+        assert_eq!(control.control_id, "S3.8");
+        assert_eq!(control.status, "Failed");
+        assert_eq!(control.standards, vec!["CIS AWS Foundations Benchmark v1.4.0"]);
+    }
+}
@@ -0,0 +1,169 @@
+//! Renders findings with the same code as the Lambda function, to iterate on the Slack
+//! message format without deploying.
+//!
+//! ```text
+//! render-finding [--dry-run] [--builder-url] [--channel <id>] [FILE]
+//! ```
+//!
+//! Reads an EventBridge event, or a single OCSF or ASFF finding, from `FILE` or stdin
+//! (when `FILE` is missing or `-`).
+
+use lambda_runtime::Error;
+use serde_json::{json, Value};
+use slack_security_hub::icons::IconRegistry;
+use slack_security_hub::links::encode;
+use slack_security_hub::mentions::OwnerMentions;
+use slack_security_hub::risk::RiskModel;
+use slack_security_hub::slack_client::{finding_blocks, SlackClient};
+use slack_security_hub::struct_event::{findings_from_json, FindingSummary};
+use std::io::Read;
+
+const USAGE: &str = "\
+Usage: render-finding [OPTIONS] [FILE]
+
+Renders the Slack message of every finding in FILE (or stdin) as Block Kit JSON.
+
+Options:
+  --dry-run         Print the Block Kit JSON (the default without --channel)
+  --builder-url     Print a Block Kit Builder link for each message
+  --channel <id>    Post the messages to the channel with the token in SLACK_BOT_TOKEN
+  -h, --help        Print this help
+
+ICON_BASE_URL, OWNER_MENTIONS, RISK_SCORING and SLACK_API_URL are read as in the Lambda function.";
+
+const BLOCK_KIT_BUILDER_URL: &str = "https://app.slack.com/block-kit-builder#";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    dry_run: bool,
+    builder_url: bool,
+    channel: Option<String>,
+    input: Option<String>,
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dry-run" => options.dry_run = true,
+            "--builder-url" => options.builder_url = true,
+            "--channel" => options.channel = Some(args.next().ok_or("--channel requires a channel ID")?),
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            "-" => options.input = None,
+            flag if flag.starts_with('-') => return Err(format!("Unknown option: {}\n\n{}", flag, USAGE)),
+            path => {
+                if options.input.replace(path.to_string()).is_some() {
+                    return Err(format!("Only one input file is supported\n\n{}", USAGE));
+                }
+            }
+        }
+    }
+
+    // Without a channel there is nothing to do but print
+    if options.channel.is_none() && !options.builder_url {
+        options.dry_run = true;
+    }
+
+    Ok(options)
+}
+
+fn read_input(input: Option<&str>) -> Result<Value, Error> {
+    let contents = match input {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read '{}': {}", path, e))?,
+        None => {
+            let mut contents = String::new();
+            std::io::stdin().read_to_string(&mut contents)?;
+            contents
+        }
+    };
+    Ok(serde_json::from_str(&contents).map_err(|e| format!("Failed to parse input JSON: {}", e))?)
+}
+
+fn builder_url(message: &Value) -> String {
+    format!("{}{}", BLOCK_KIT_BUILDER_URL, encode(&message.to_string()))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Error> {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+
+    let findings = findings_from_json(read_input(options.input.as_deref())?)?;
+    let icons = IconRegistry::from_env();
+    let owner_mentions = OwnerMentions::from_env()?;
+    let risk_model = RiskModel::from_env()?;
+    let slack = match &options.channel {
+        Some(_) => {
+            let token = std::env::var("SLACK_BOT_TOKEN")
+                .map_err(|_| "SLACK_BOT_TOKEN environment variable not set")?;
+            Some(SlackClient::from_env(token)?)
+        }
+        None => None,
+    };
+
+    for finding in &findings {
+        let mut summary = FindingSummary::from_finding(finding);
+        summary.owners = owner_mentions.owners(&summary);
+        summary.risk = Some(risk_model.score(&summary));
+        let message = json!({ "blocks": finding_blocks(&summary, &icons) });
+
+        if options.dry_run {
+            println!("{}", serde_json::to_string_pretty(&message)?);
+        }
+        if options.builder_url {
+            println!("{}", builder_url(&message));
+        }
+        if let (Some(slack), Some(channel)) = (&slack, &options.channel) {
+            let posted = slack.post_slack_message(channel, &summary).await?;
+            eprintln!("Posted {} to {} (ts {})", summary.uid, posted.channel, posted.ts);
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|a| a.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse_args(args(&["finding.json"])).unwrap(),
+            Options { dry_run: true, input: Some("finding.json".to_string()), ..Default::default() }
+        );
+        assert_eq!(
+            parse_args(args(&["--channel", "C123", "-"])).unwrap(),
+            Options { channel: Some("C123".to_string()), ..Default::default() }
+        );
+        assert_eq!(
+            parse_args(args(&["--builder-url"])).unwrap(),
+            Options { builder_url: true, ..Default::default() }
+        );
+        assert!(parse_args(args(&["--channel"])).is_err());
+        assert!(parse_args(args(&["--verbose"])).is_err());
+        assert!(parse_args(args(&["a.json", "b.json"])).is_err());
+    }
+
+    #[test]
+    fn test_builder_url_encodes_message() {
+        let url = builder_url(&json!({ "blocks": [{ "type": "divider" }] }));
+
+        assert_eq!(
+            url,
+            "https://app.slack.com/block-kit-builder#%7B%22blocks%22%3A%5B%7B%22type%22%3A%22divider%22%7D%5D%7D"
+        );
+    }
+}
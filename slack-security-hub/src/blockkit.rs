@@ -0,0 +1,328 @@
+use serde_json::{json, Value};
+
+/// Block Kit limits, counted in characters. Slack rejects the whole message when
+/// any of them is exceeded.
+pub const MAX_BLOCKS: usize = 50;
+pub const MAX_HEADER_TEXT: usize = 150;
+pub const MAX_SECTION_TEXT: usize = 3000;
+pub const MAX_SECTION_FIELDS: usize = 10;
+pub const MAX_FIELD_TEXT: usize = 2000;
+pub const MAX_CONTEXT_ELEMENTS: usize = 10;
+pub const MAX_ACTION_ELEMENTS: usize = 25;
+pub const MAX_BUTTON_TEXT: usize = 75;
+pub const MAX_ALT_TEXT: usize = 2000;
+
+/// Sections a single text is split into at most, so that long texts leave room for the
+/// rest of the message.
+pub const MAX_TEXT_SECTIONS: usize = 5;
+
+const ELLIPSIS: char = '…';
+
+/// Shortens `text` to at most `max` characters, ending with an ellipsis when cut.
+pub fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max.saturating_sub(1)).collect();
+    truncated.push(ELLIPSIS);
+    truncated
+}
+
+/// Splits `text` into chunks of at most `max` characters, preferring to break after a
+/// newline, then after a space. Concatenating the chunks gives back the original text.
+pub fn split_text(text: &str, max: usize) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut rest: Vec<char> = text.chars().collect();
+
+    while rest.len() > max {
+        let window = &rest[..max];
+        // Only break early when it keeps at least half of the window
+        let break_after = |is_break: fn(&char) -> bool| window.iter()
+            .rposition(is_break)
+            .map(|i| i + 1)
+            .filter(|&i| i > max / 2);
+        let cut = break_after(|c| *c == '\n')
+            .or_else(|| break_after(|c| c.is_whitespace()))
+            .unwrap_or(max);
+        chunks.push(rest.drain(..cut).collect());
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest.into_iter().collect());
+    }
+    chunks
+}
+
+pub fn header(text: &str) -> Value {
+    json!({
+        "type": "header",
+        "text": {
+            "type": "plain_text",
+            "text": truncate(text, MAX_HEADER_TEXT),
+            "emoji": true
+        }
+    })
+}
+
+/// One mrkdwn section per `MAX_SECTION_TEXT` chunk of `text`, up to `MAX_TEXT_SECTIONS`
+/// sections with the last one ending in an ellipsis when the text is cut. The accessory,
+/// if any, is attached to the first section only.
+pub fn mrkdwn_sections(text: &str, accessory: Option<Value>) -> Vec<Value> {
+    let mut accessory = accessory;
+    let mut chunks = split_text(text, MAX_SECTION_TEXT);
+    if chunks.len() > MAX_TEXT_SECTIONS {
+        chunks.truncate(MAX_TEXT_SECTIONS);
+        let last = chunks.last_mut().expect("MAX_TEXT_SECTIONS is not zero");
+        *last = truncate(&format!("{}{}", last, ELLIPSIS), MAX_SECTION_TEXT);
+    }
+    chunks
+        .into_iter()
+        .map(|chunk| {
+            let mut section = json!({
+                "type": "section",
+                "text": {
+                    "type": "mrkdwn",
+                    "text": chunk
+                }
+            });
+            if let Some(accessory) = accessory.take() {
+                section["accessory"] = accessory;
+            }
+            section
+        })
+        .collect()
+}
+
+/// Truncates every element of the blocks to its Block Kit limit and caps the number
+/// of blocks. The overflow is dropped from the content before the footer (the trailing
+/// buttons, context lines and dividers) and replaced with a context note.
+pub fn enforce_limits(blocks: Vec<Value>) -> Vec<Value> {
+    let mut blocks: Vec<Value> = blocks.into_iter().map(enforce_block_limits).collect();
+
+    if blocks.len() > MAX_BLOCKS {
+        let dropped = blocks.len() - (MAX_BLOCKS - 1);
+        let footer_len = blocks.iter().rev().take_while(|block| is_footer(block)).count().min(MAX_BLOCKS / 2);
+        let footer = blocks.split_off(blocks.len() - footer_len);
+        blocks.truncate(blocks.len() - dropped);
+        blocks.push(json!({
+            "type": "context",
+            "elements": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("_{} more blocks omitted_", dropped)
+                }
+            ]
+        }));
+        blocks.extend(footer);
+    }
+    blocks
+}
+
+/// Blocks that close a message: buttons, context lines and dividers.
+fn is_footer(block: &Value) -> bool {
+    match block["type"].as_str() {
+        Some("actions" | "context" | "divider") => true,
+        Some("section") => block["accessory"]["type"] == "button",
+        _ => false,
+    }
+}
+
+fn enforce_block_limits(mut block: Value) -> Value {
+    // `pointer_mut` rather than indexing, which would insert null for missing keys
+    match block["type"].as_str() {
+        Some("header") => truncate_text(&mut block, "/text/text", MAX_HEADER_TEXT),
+        Some("section") => {
+            truncate_text(&mut block, "/text/text", MAX_SECTION_TEXT);
+            if let Some(fields) = block.pointer_mut("/fields").and_then(Value::as_array_mut) {
+                fields.truncate(MAX_SECTION_FIELDS);
+                for field in fields {
+                    truncate_text(field, "/text", MAX_FIELD_TEXT);
+                }
+            }
+            if let Some(accessory) = block.pointer_mut("/accessory") {
+                enforce_element_limits(accessory);
+            }
+        }
+        Some("context") => {
+            if let Some(elements) = block.pointer_mut("/elements").and_then(Value::as_array_mut) {
+                elements.truncate(MAX_CONTEXT_ELEMENTS);
+                for element in elements {
+                    truncate_text(element, "/text", MAX_SECTION_TEXT);
+                    enforce_element_limits(element);
+                }
+            }
+        }
+        Some("actions") => {
+            if let Some(elements) = block.pointer_mut("/elements").and_then(Value::as_array_mut) {
+                elements.truncate(MAX_ACTION_ELEMENTS);
+                for element in elements {
+                    enforce_element_limits(element);
+                }
+            }
+        }
+        _ => {}
+    }
+    block
+}
+
+fn enforce_element_limits(element: &mut Value) {
+    match element["type"].as_str() {
+        Some("button") => truncate_text(element, "/text/text", MAX_BUTTON_TEXT),
+        Some("image") => truncate_text(element, "/alt_text", MAX_ALT_TEXT),
+        _ => {}
+    }
+}
+
+fn truncate_text(value: &mut Value, pointer: &str, max: usize) {
+    if let Some(text) = value.pointer_mut(pointer)
+        && let Some(truncated) = text.as_str().filter(|t| t.chars().count() > max).map(|t| truncate(t, max))
+    {
+        *text = Value::String(truncated);
+    }
+}
+
+/// Returns the first Block Kit limit the blocks exceed, if any.
+#[cfg(test)]
+pub fn validate(blocks: &[Value]) -> Result<(), String> {
+    if blocks.len() > MAX_BLOCKS {
+        return Err(format!("{} blocks exceed the limit of {}", blocks.len(), MAX_BLOCKS));
+    }
+
+    let check = |value: &Value, max: usize, what: &str| match value.as_str() {
+        Some(text) if text.chars().count() > max => {
+            Err(format!("{} has {} characters, limit is {}", what, text.chars().count(), max))
+        }
+        Some("") => Err(format!("{} is empty", what)),
+        _ => Ok(()),
+    };
+
+    for block in blocks {
+        match block["type"].as_str() {
+            Some("header") => check(&block["text"]["text"], MAX_HEADER_TEXT, "header text")?,
+            Some("section") => {
+                check(&block["text"]["text"], MAX_SECTION_TEXT, "section text")?;
+                let fields = block["fields"].as_array().map(Vec::len).unwrap_or(0);
+                if fields > MAX_SECTION_FIELDS {
+                    return Err(format!("{} section fields exceed the limit of {}", fields, MAX_SECTION_FIELDS));
+                }
+            }
+            Some("context") => {
+                let elements = block["elements"].as_array().map(Vec::len).unwrap_or(0);
+                if elements > MAX_CONTEXT_ELEMENTS {
+                    return Err(format!("{} context elements exceed the limit of {}", elements, MAX_CONTEXT_ELEMENTS));
+                }
+            }
+            Some("actions") => {
+                for element in block["elements"].as_array().into_iter().flatten() {
+                    check(&element["text"]["text"], MAX_BUTTON_TEXT, "button text")?;
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_truncate_adds_ellipsis() {
+        assert_eq!(truncate("short", 10), "short");
+        assert_eq!(truncate("exactly10!", 10), "exactly10!");
+        assert_eq!(truncate("this is too long", 10), "this is t…");
+        assert_eq!(truncate("ééééé", 3), "éé…");
+    }
+
+    #[test]
+    fn test_split_prefers_line_breaks() {
+        let text = format!("{}\n{}", "a".repeat(20), "b".repeat(20));
+        assert_eq!(split_text(&text, 30), vec![format!("{}\n", "a".repeat(20)), "b".repeat(20)]);
+        assert_eq!(split_text("", 30), vec![""]);
+    }
+
+    #[test]
+    fn test_accessory_only_on_first_section() {
+        let sections = mrkdwn_sections(&"word ".repeat(1000), Some(json!({"type": "image"})));
+
+        assert_eq!(sections.len(), 2);
+        assert_eq!(sections[0]["accessory"]["type"], "image");
+        assert!(sections[1].get("accessory").is_none());
+    }
+
+    #[test]
+    fn test_enforce_limits_caps_blocks_and_fields() {
+        let section = json!({
+            "type": "section",
+            "fields": (0..12).map(|i| json!({"type": "mrkdwn", "text": i.to_string()})).collect::<Vec<_>>()
+        });
+        let blocks = enforce_limits(vec![section; 60]);
+
+        assert_eq!(blocks.len(), MAX_BLOCKS);
+        assert_eq!(blocks[0]["fields"].as_array().unwrap().len(), MAX_SECTION_FIELDS);
+        assert_eq!(blocks[MAX_BLOCKS - 1]["elements"][0]["text"], "_11 more blocks omitted_");
+        assert!(blocks[0].get("text").is_none());
+        assert!(validate(&blocks).is_ok());
+    }
+
+    #[test]
+    fn test_enforce_limits_keeps_footer() {
+        let section = json!({"type": "section", "text": {"type": "mrkdwn", "text": "content"}});
+        let mut blocks = vec![section; 60];
+        blocks.push(json!({"type": "actions", "elements": []}));
+        blocks.push(json!({"type": "divider"}));
+        let blocks = enforce_limits(blocks);
+
+        assert_eq!(blocks.len(), MAX_BLOCKS);
+        assert_eq!(blocks[MAX_BLOCKS - 3]["elements"][0]["text"], "_13 more blocks omitted_");
+        assert_eq!(blocks[MAX_BLOCKS - 2]["type"], "actions");
+        assert_eq!(blocks[MAX_BLOCKS - 1]["type"], "divider");
+    }
+
+    #[test]
+    fn test_long_text_sections_are_capped() {
+        let sections = mrkdwn_sections(&"word ".repeat(10_000), None);
+
+        assert_eq!(sections.len(), MAX_TEXT_SECTIONS);
+        assert!(sections[MAX_TEXT_SECTIONS - 1]["text"]["text"].as_str().unwrap().ends_with(ELLIPSIS));
+        assert!(validate(&sections).is_ok());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_truncate_respects_limit(text in ".{0,400}", max in 1usize..200) {
+            let truncated = truncate(&text, max);
+            prop_assert!(truncated.chars().count() <= max);
+            if text.chars().count() <= max {
+                prop_assert_eq!(truncated, text);
+            }
+        }
+
+        #[test]
+        fn prop_split_is_lossless_and_bounded(text in "(\\PC{0,40}[ \n]?){0,200}", max in 10usize..300) {
+            let chunks = split_text(&text, max);
+            prop_assert!(chunks.iter().all(|c| c.chars().count() <= max));
+            prop_assert!(chunks.len() == 1 || chunks.iter().all(|c| !c.is_empty()));
+            prop_assert_eq!(chunks.concat(), text);
+        }
+
+        #[test]
+        fn prop_enforced_blocks_validate(
+            header_len in 0usize..500,
+            section_len in 0usize..8000,
+            button_len in 0usize..200,
+            count in 1usize..80,
+        ) {
+            let mut blocks = vec![header(&"h".repeat(header_len.max(1)))];
+            blocks.extend(mrkdwn_sections(&"s".repeat(section_len.max(1)), None));
+            for _ in 0..count {
+                blocks.push(json!({
+                    "type": "actions",
+                    "elements": [{"type": "button", "text": {"type": "plain_text", "text": "b".repeat(button_len.max(1))}}]
+                }));
+            }
+            prop_assert!(validate(&enforce_limits(blocks)).is_ok());
+        }
+    }
+}
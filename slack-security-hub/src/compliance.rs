@@ -0,0 +1,224 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::fmt;
+use crate::struct_event::{Compliance, Finding};
+
+const CONTROL_DOCS_BASE_URL: &str = "https://docs.aws.amazon.com/securityhub/latest/userguide";
+
+/// Broad kind of a finding, used to route control failures separately from threat
+/// detections.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingCategory {
+    /// Security Hub CSPM control checks.
+    Compliance,
+    /// Threat detections, e.g. GuardDuty.
+    Threat,
+    Vulnerability,
+    /// Sensitive data discoveries, e.g. Macie.
+    DataSecurity,
+    #[default]
+    Other,
+}
+
+impl FindingCategory {
+    /// Uses the OCSF class of the finding, falling back to its compliance section and
+    /// finding types for findings normalized from ASFF.
+    pub fn from_finding(finding: &Finding) -> Self {
+        match finding.class_uid {
+            Some(2002) => return Self::Vulnerability,
+            Some(2003) => return Self::Compliance,
+            Some(2004) => return Self::Threat,
+            Some(2006) => return Self::DataSecurity,
+            _ => {}
+        }
+
+        if finding.compliance.as_ref().is_some_and(|c| c.control.is_some()) {
+            return Self::Compliance;
+        }
+
+        let types = finding.finding_info.as_ref().and_then(|fi| fi.types.as_deref()).unwrap_or_default();
+        let has_type = |prefix: &str| types.iter().any(|t| t.starts_with(prefix));
+        if has_type("TTPs") || has_type("Effects") || has_type("Unusual Behaviors") {
+            Self::Threat
+        } else if has_type("Software and Configuration Checks/Vulnerabilities") {
+            Self::Vulnerability
+        } else if has_type("Sensitive Data Identifications") {
+            Self::DataSecurity
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl fmt::Display for FindingCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Compliance => "Compliance",
+            Self::Threat => "Threat",
+            Self::Vulnerability => "Vulnerability",
+            Self::DataSecurity => "Data security",
+            Self::Other => "Other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Security Hub CSPM control a compliance finding was produced by.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ControlSummary {
+    /// Control ID, e.g. `S3.8`.
+    pub control_id: String,
+    /// Display names of the standards the control belongs to.
+    pub standards: Vec<String>,
+    /// Compliance status, e.g. `Failed`.
+    pub status: String,
+    /// Related requirements, e.g. `PCI DSS v3.2.1/1.2.1`.
+    pub requirements: Vec<String>,
+}
+
+impl ControlSummary {
+    pub fn from_compliance(compliance: &Compliance) -> Option<Self> {
+        Some(Self {
+            control_id: compliance.control.clone()?,
+            standards: compliance.standards.iter().flatten().map(|s| standard_name(s)).collect(),
+            status: compliance.status.as_deref().map(status_name).unwrap_or_else(|| "Unknown".to_string()),
+            requirements: compliance.requirements.clone().unwrap_or_default(),
+        })
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.status.eq_ignore_ascii_case("Failed")
+    }
+
+    /// Remediation page of the control in the Security Hub user guide, e.g.
+    /// `.../s3-controls.html#s3-8` for `S3.8`.
+    pub fn docs_url(&self) -> Option<String> {
+        let (service, number) = self.control_id.split_once('.')?;
+        let service = service.to_ascii_lowercase();
+        Some(format!("{}/{}-controls.html#{}-{}", CONTROL_DOCS_BASE_URL, service, service, number))
+    }
+
+    /// Context block showing the control, its standards and its compliance status.
+    pub fn badge_block(&self) -> Value {
+        let mut parts = vec![format!(":clipboard: *{}*", self.control_id)];
+        if !self.standards.is_empty() {
+            parts.push(self.standards.join(", "));
+        }
+        parts.push(format!("{} *{}*", status_emoji(&self.status), self.status));
+
+        let mut elements = vec![json!({
+            "type": "mrkdwn",
+            "text": parts.join("  |  ")
+        })];
+        if !self.requirements.is_empty() {
+            elements.push(json!({
+                "type": "mrkdwn",
+                "text": format!("Requirements: {}", self.requirements.join(", "))
+            }));
+        }
+
+        json!({
+            "type": "context",
+            "elements": elements
+        })
+    }
+}
+
+/// Display name of a standard from its ARN or ID, e.g.
+/// `arn:aws:securityhub:us-east-1::standards/pci-dss/v/3.2.1` becomes `PCI DSS v3.2.1`.
+pub fn standard_name(standard: &str) -> String {
+    let Some(path) = standard.split_once("standards/").or_else(|| standard.split_once("ruleset/")).map(|(_, p)| p) else {
+        return standard.to_string();
+    };
+    let (slug, version) = match path.split_once("/v/") {
+        Some((slug, version)) => (slug, Some(version)),
+        None => (path, None),
+    };
+
+    let name = match slug {
+        "aws-foundational-security-best-practices" => "AWS Foundational Security Best Practices",
+        "cis-aws-foundations-benchmark" => "CIS AWS Foundations Benchmark",
+        "pci-dss" => "PCI DSS",
+        "nist-800-53" => "NIST SP 800-53",
+        "nist-800-171" => "NIST SP 800-171",
+        "aws-resource-tagging-standard" => "AWS Resource Tagging Standard",
+        "service-managed-aws-control-tower" => "AWS Control Tower",
+        other => other,
+    };
+
+    match version {
+        Some(version) => format!("{} v{}", name, version),
+        None => name.to_string(),
+    }
+}
+
+/// Normalizes OCSF (`Fail`) and ASFF (`FAILED`) statuses to `Failed`, `Passed`, ...
+fn status_name(status: &str) -> String {
+    match status.to_ascii_uppercase().replace(' ', "_").as_str() {
+        "FAIL" | "FAILED" => "Failed".to_string(),
+        "PASS" | "PASSED" => "Passed".to_string(),
+        "WARNING" => "Warning".to_string(),
+        "NOT_AVAILABLE" | "NOT_APPLICABLE" | "UNKNOWN" => "Not available".to_string(),
+        _ => status.to_string(),
+    }
+}
+
+fn status_emoji(status: &str) -> &'static str {
+    match status {
+        "Failed" => ":x:",
+        "Passed" => ":white_check_mark:",
+        "Warning" => ":warning:",
+        _ => ":grey_question:",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+
+    #[test]
+    fn test_standard_names() {
+        assert_eq!(
+            standard_name("arn:aws:securityhub:us-east-1::standards/aws-foundational-security-best-practices/v/1.0.0"),
+            "AWS Foundational Security Best Practices v1.0.0"
+        );
+        assert_eq!(standard_name("arn:aws:securityhub:::ruleset/cis-aws-foundations-benchmark/v/1.2.0"), "CIS AWS Foundations Benchmark v1.2.0");
+        assert_eq!(standard_name("standards/pci-dss/v/3.2.1"), "PCI DSS v3.2.1");
+        assert_eq!(standard_name("Custom standard"), "Custom standard");
+    }
+
+    #[test]
+    fn test_control_from_compliance_finding() {
+        let summary = summary(include_str!("../fixtures/cspm_s3_control_failed.json"));
+        let control = summary.control.as_ref().unwrap();
+
+        assert_eq!(summary.category, FindingCategory::Compliance);
+        assert_eq!(control.control_id, "S3.8");
+        assert_eq!(control.standards, vec![
+            "AWS Foundational Security Best Practices v1.0.0",
+            "NIST SP 800-53 v5.0.0",
+        ]);
+        assert!(control.is_failed());
+        assert_eq!(
+            control.docs_url().unwrap(),
+            "https://docs.aws.amazon.com/securityhub/latest/userguide/s3-controls.html#s3-8"
+        );
+
+        let badge = control.badge_block();
+        assert_eq!(
+            badge["elements"][0]["text"],
+            ":clipboard: *S3.8*  |  AWS Foundational Security Best Practices v1.0.0, NIST SP 800-53 v5.0.0  |  :x: *Failed*"
+        );
+        assert_eq!(badge["elements"][1]["text"], "Requirements: NIST.800-53.r5 AC-21, NIST.800-53.r5 AC-3");
+    }
+
+    #[test]
+    fn test_categories() {
+        assert_eq!(summary(include_str!("../fixtures/guardduty_prod_critical.json")).category, FindingCategory::Threat);
+        assert_eq!(summary(include_str!("../fixtures/inspector_dev_high.json")).category, FindingCategory::Vulnerability);
+        assert_eq!(summary(include_str!("../fixtures/macie_sensitive_data.json")).category, FindingCategory::DataSecurity);
+        assert!(summary(include_str!("../fixtures/guardduty_prod_critical.json")).control.is_none());
+    }
+}
@@ -0,0 +1,339 @@
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use crate::blockkit;
+use crate::digest::severity_emoji;
+use crate::severity::Severity;
+use crate::slack_client::SlackMessageRef;
+use crate::struct_event::FindingSummary;
+
+/// Findings listed in the incident message before the rest are summarized.
+const MAX_LISTED_FINDINGS: usize = 10;
+
+/// Findings on the same resource within `window_seconds` of each other are grouped into
+/// one incident.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorrelationPolicy {
+    pub window_seconds: i64,
+}
+
+impl Default for CorrelationPolicy {
+    fn default() -> Self {
+        Self { window_seconds: 30 * 60 }
+    }
+}
+
+impl CorrelationPolicy {
+    /// Reads `CORRELATION_WINDOW_MINUTES` (defaults to 30).
+    pub fn from_env() -> Result<Self, Error> {
+        match std::env::var("CORRELATION_WINDOW_MINUTES") {
+            Ok(minutes) => Ok(Self::from_minutes(&minutes)
+                .map_err(|e| format!("Invalid CORRELATION_WINDOW_MINUTES '{}': {}", minutes, e))?),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Parses a window of a positive number of minutes.
+    fn from_minutes(minutes: &str) -> Result<Self, String> {
+        let minutes = minutes.trim().parse::<i64>().map_err(|e| e.to_string())?;
+        if minutes <= 0 {
+            return Err("the window must be at least one minute".to_string());
+        }
+        let window_seconds = minutes.checked_mul(60).ok_or("the window is too long")?;
+        Ok(Self { window_seconds })
+    }
+
+    /// An incident stays open while its last finding is within the window.
+    pub fn is_open(&self, incident: &Incident, now: i64) -> bool {
+        now - incident.last_seen <= self.window_seconds
+    }
+}
+
+/// Findings on one resource, posted as thread replies under a parent message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Incident {
+    pub resource_uid: String,
+    pub resource_type: String,
+    pub account: String,
+    pub region: String,
+    /// Unix timestamps (seconds) of the first and last finding.
+    pub opened_at: i64,
+    pub last_seen: i64,
+    pub max_severity: Severity,
+    /// Products in order of their first finding.
+    pub products: Vec<String>,
+    pub findings: Vec<IncidentFinding>,
+    /// Parent message of the incident in each routed channel.
+    #[serde(default)]
+    pub parents: Vec<IncidentMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentFinding {
+    pub uid: String,
+    pub title: String,
+    pub severity: Severity,
+    pub product: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IncidentMessage {
+    /// Channel as selected by the routing rules, e.g. `#sec-incidents`.
+    pub channel: String,
+    pub message: SlackMessageRef,
+}
+
+/// Resource findings are correlated on, `None` for findings without a resource.
+pub fn resource_key(summary: &FindingSummary) -> Option<&str> {
+    summary.resources.first()
+        .map(|r| r.uid.as_str())
+        .filter(|uid| !uid.is_empty() && *uid != "unknown-resource")
+}
+
+impl Incident {
+    /// Opens an incident on the resource of the finding. The finding itself is added
+    /// with `add`.
+    pub fn open(summary: &FindingSummary, now: i64) -> Self {
+        let resource = summary.resources.first();
+        Self {
+            resource_uid: resource.map(|r| r.uid.clone()).unwrap_or_default(),
+            resource_type: resource.map(|r| r.resource_type.clone()).unwrap_or_default(),
+            account: summary.account_label(),
+            region: summary.region.clone(),
+            opened_at: now,
+            last_seen: now,
+            max_severity: summary.severity,
+            products: Vec::new(),
+            findings: Vec::new(),
+            parents: Vec::new(),
+        }
+    }
+
+    /// Adds the finding, replacing an earlier emission of the same finding.
+    pub fn add(&mut self, summary: &FindingSummary, now: i64) {
+        let finding = IncidentFinding {
+            uid: summary.uid.clone(),
+            title: summary.title.clone(),
+            severity: summary.severity,
+            product: summary.product_name.clone(),
+        };
+        match self.findings.iter_mut().find(|f| f.uid == finding.uid) {
+            Some(existing) => *existing = finding,
+            None => self.findings.push(finding),
+        }
+
+        if !self.products.contains(&summary.product_name) {
+            self.products.push(summary.product_name.clone());
+        }
+        self.max_severity = self.findings.iter().map(|f| f.severity).max().unwrap_or(summary.severity);
+        self.last_seen = self.last_seen.max(now);
+    }
+
+    pub fn parent(&self, channel: &str) -> Option<&SlackMessageRef> {
+        self.parents.iter()
+            .find(|p| p.channel == channel)
+            .map(|p| &p.message)
+    }
+}
+
+/// Parent message of an incident: the header shows the highest severity and the products,
+/// followed by the findings from most to least severe.
+pub fn incident_blocks(incident: &Incident) -> Vec<Value> {
+    let mut findings: Vec<&IncidentFinding> = incident.findings.iter().collect();
+    // Stable sort keeps the arrival order among findings of the same severity
+    findings.sort_by_key(|f| Reverse(f.severity));
+    let mut lines: Vec<String> = findings.iter()
+        .take(MAX_LISTED_FINDINGS)
+        .map(|f| format!("{} *{}*  {}  _({})_", severity_emoji(f.severity), f.severity, f.title, f.product))
+        .collect();
+    if findings.len() > MAX_LISTED_FINDINGS {
+        lines.push(format!("+{} more findings", findings.len() - MAX_LISTED_FINDINGS));
+    }
+
+    let mut blocks = vec![
+        blockkit::header(&format!(
+            "{} {} incident: {}",
+            severity_emoji(incident.max_severity),
+            incident.max_severity,
+            incident.products.join(", ")
+        )),
+        json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!(
+                    "*{}* findings on `{}`\n*Resource type:* {}  |  *Account:* {}  |  *Region:* {}",
+                    incident.findings.len(),
+                    incident.resource_uid,
+                    incident.resource_type,
+                    incident.account,
+                    incident.region
+                )
+            }
+        }),
+    ];
+    blocks.extend(blockkit::mrkdwn_sections(&lines.join("\n"), None));
+    blocks.push(json!({
+        "type": "context",
+        "elements": [
+            {
+                "type": "mrkdwn",
+                "text": "Full details of every finding are in the thread."
+            }
+        ]
+    }));
+
+    blockkit::enforce_limits(blocks)
+}
+
+/// Persistence for open incidents, keyed by resource uid.
+#[async_trait]
+pub trait IncidentStore: Send + Sync {
+    async fn get(&self, resource_uid: &str) -> Result<Option<Incident>, Error>;
+    async fn put(&self, incident: &Incident) -> Result<(), Error>;
+}
+
+/// Correlation is enabled when `CORRELATION_TABLE_NAME` is set.
+pub fn store_from_env(config: &SdkConfig) -> Option<Box<dyn IncidentStore>> {
+    std::env::var("CORRELATION_TABLE_NAME").ok().map(|table_name| {
+        Box::new(DynamoDbIncidentStore::new(DynamoDbClient::new(config), table_name)) as Box<dyn IncidentStore>
+    })
+}
+
+/// DynamoDB table with a string partition key `resource_uid` and the incident as JSON in
+/// `incident`.
+pub struct DynamoDbIncidentStore {
+    client: DynamoDbClient,
+    table_name: String,
+}
+
+impl DynamoDbIncidentStore {
+    pub fn new(client: DynamoDbClient, table_name: String) -> Self {
+        Self { client, table_name }
+    }
+}
+
+#[async_trait]
+impl IncidentStore for DynamoDbIncidentStore {
+    async fn get(&self, resource_uid: &str) -> Result<Option<Incident>, Error> {
+        let response = self.client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("resource_uid", AttributeValue::S(resource_uid.to_string()))
+            .consistent_read(true)
+            .send()
+            .await?;
+
+        let incident = response.item()
+            .and_then(|item| item.get("incident"))
+            .and_then(|value| value.as_s().ok());
+
+        match incident {
+            Some(json) => Ok(Some(serde_json::from_str(json)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, incident: &Incident) -> Result<(), Error> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .item("resource_uid", AttributeValue::S(incident.resource_uid.clone()))
+            .item("incident", AttributeValue::S(serde_json::to_string(incident)?))
+            .item("updated_at", AttributeValue::N(incident.last_seen.to_string()))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// In-memory store used by tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryIncidentStore {
+    incidents: std::sync::Mutex<std::collections::HashMap<String, Incident>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl IncidentStore for MemoryIncidentStore {
+    async fn get(&self, resource_uid: &str) -> Result<Option<Incident>, Error> {
+        Ok(self.incidents.lock().unwrap().get(resource_uid).cloned())
+    }
+
+    async fn put(&self, incident: &Incident) -> Result<(), Error> {
+        self.incidents.lock().unwrap().insert(incident.resource_uid.clone(), incident.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+
+    /// The Inspector finding moved onto the instance of the GuardDuty finding.
+    fn findings_on_one_instance() -> (FindingSummary, FindingSummary) {
+        let guardduty = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let mut inspector = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        inspector.resources[0].uid = guardduty.resources[0].uid.clone();
+        (guardduty, inspector)
+    }
+
+    #[test]
+    fn test_window_minutes() {
+        assert_eq!(CorrelationPolicy::from_minutes(" 45 ").unwrap().window_seconds, 45 * 60);
+        assert!(CorrelationPolicy::from_minutes("0").is_err());
+        assert!(CorrelationPolicy::from_minutes("-5").is_err());
+        assert!(CorrelationPolicy::from_minutes(&i64::MAX.to_string()).is_err());
+        assert!(CorrelationPolicy::from_minutes("half an hour").is_err());
+    }
+
+    #[test]
+    fn test_incident_aggregates_severity_and_products() {
+        let (guardduty, inspector) = findings_on_one_instance();
+        let mut incident = Incident::open(&inspector, 1_000);
+        incident.add(&inspector, 1_000);
+        incident.add(&guardduty, 1_300);
+        incident.add(&inspector, 1_400);
+
+        assert_eq!(incident.findings.len(), 2);
+        assert_eq!(incident.max_severity, Severity::Critical);
+        assert_eq!(incident.products, vec!["Inspector", "GuardDuty"]);
+        assert_eq!((incident.opened_at, incident.last_seen), (1_000, 1_400));
+
+        let blocks = incident_blocks(&incident);
+        assert_eq!(blocks[0]["text"]["text"], ":red_circle: Critical incident: Inspector, GuardDuty");
+        assert!(blocks[1]["text"]["text"].as_str().unwrap().starts_with(&format!("*2* findings on `{}`", incident.resource_uid)));
+        assert!(blocks[2]["text"]["text"].as_str().unwrap().starts_with(&format!(":red_circle: *Critical*  {}", guardduty.title)));
+    }
+
+    #[test]
+    fn test_correlation_window() {
+        let (guardduty, _) = findings_on_one_instance();
+        let incident = Incident::open(&guardduty, 1_000);
+        let policy = CorrelationPolicy { window_seconds: 600 };
+
+        assert!(policy.is_open(&incident, 1_600));
+        assert!(!policy.is_open(&incident, 1_601));
+        assert_eq!(resource_key(&guardduty), Some(incident.resource_uid.as_str()));
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_round_trip() {
+        let (guardduty, _) = findings_on_one_instance();
+        let store = MemoryIncidentStore::default();
+        let mut incident = Incident::open(&guardduty, 1_000);
+        incident.add(&guardduty, 1_000);
+
+        assert_eq!(store.get(&incident.resource_uid).await.unwrap(), None);
+        store.put(&incident).await.unwrap();
+        assert_eq!(store.get(&incident.resource_uid).await.unwrap(), Some(incident));
+    }
+}
@@ -0,0 +1,574 @@
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use lambda_runtime::{tracing, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use crate::notifier::{MessageRef, Notifier};
+use crate::state_store::{FindingState, FindingStateStore};
+use crate::struct_event::{Finding, FindingSummary};
+use crate::s3::{self, S3Uri};
+
+/// `detail-type` of the event that replays the failed deliveries instead of processing
+/// findings.
+pub const REPLAY_DETAIL_TYPE: &str = "Replay Failed Deliveries";
+
+/// SQS returns at most 10 messages per receive call.
+const SQS_MAX_MESSAGES: i32 = 10;
+/// Long polling queries every SQS server, so an empty receive means the queue is drained.
+const SQS_WAIT_SECONDS: i32 = 2;
+/// Received letters stay hidden for the longest a Lambda invocation can run, so they do not
+/// come back while the replay is still going. Failed letters come back after it.
+const SQS_VISIBILITY_SECONDS: i32 = 15 * 60;
+
+/// A finding a sink failed to deliver, with everything needed to deliver it again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailedDelivery {
+    /// Idempotency key derived from the sink, the finding and its notified state, so the
+    /// same failure stored twice is replayed once.
+    pub id: String,
+    pub notifier: String,
+    pub error: String,
+    /// Unix timestamp (seconds) of the failed attempt.
+    pub failed_at: i64,
+    /// Payload the sink rendered for the finding.
+    pub payload: Value,
+    pub finding: Finding,
+    pub summary: FindingSummary,
+    pub state: FindingState,
+    /// Previous status and messages when the failed delivery was a status change.
+    #[serde(default)]
+    pub status_change: Option<(String, Vec<MessageRef>)>,
+    /// Channels the sink failed to deliver to when others succeeded, empty when the whole
+    /// delivery is replayed.
+    #[serde(default)]
+    pub channels: Vec<String>,
+}
+
+impl FailedDelivery {
+    pub fn new(
+        notifier: &dyn Notifier,
+        error: &Error,
+        failed_at: i64,
+        finding: &Finding,
+        summary: &FindingSummary,
+        state: &FindingState,
+        status_change: Option<&(String, Vec<MessageRef>)>,
+    ) -> Self {
+        let mut hasher = Sha256::new();
+        for part in [notifier.name(), &state.uid, &state.status, &state.fingerprint] {
+            hasher.update(part.as_bytes());
+            hasher.update([0u8]);
+        }
+
+        Self {
+            id: hex::encode(hasher.finalize()),
+            notifier: notifier.name().to_string(),
+            error: error.to_string(),
+            failed_at,
+            payload: notifier.render(summary),
+            finding: finding.clone(),
+            summary: summary.clone(),
+            state: state.clone(),
+            status_change: status_change.cloned(),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Limits the replay to `channels`, the channels that failed when others succeeded.
+    pub fn with_channels(mut self, channels: &[String]) -> Self {
+        if channels.is_empty() {
+            return self;
+        }
+        // Only the messages of the failed channels are updated again
+        if let Some((_, messages)) = &mut self.status_change {
+            messages.retain(|m| m.sink != self.notifier || channels.contains(&m.channel));
+        }
+        self.channels = channels.to_vec();
+        self
+    }
+}
+
+/// A stored failed delivery and the store-specific handle used to delete it.
+#[derive(Debug, Clone)]
+pub struct DeadLetter {
+    pub handle: String,
+    pub delivery: FailedDelivery,
+}
+
+/// Persistence for failed deliveries until they are replayed.
+#[async_trait]
+pub trait DeadLetterStore: Send + Sync {
+    async fn put(&self, delivery: &FailedDelivery) -> Result<(), Error>;
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error>;
+    async fn delete(&self, letter: &DeadLetter) -> Result<(), Error>;
+}
+
+/// Builds the configured dead-letter store: `DEAD_LETTER_QUEUE_URL` selects SQS,
+/// `DEAD_LETTER_S3_URI` (`s3://bucket/prefix`) S3 and `DEAD_LETTER_DIR` a local directory.
+/// Returns `None` when failed deliveries are only logged.
+pub fn from_env(config: &SdkConfig) -> Result<Option<Box<dyn DeadLetterStore>>, Error> {
+    if let Ok(queue_url) = std::env::var("DEAD_LETTER_QUEUE_URL") {
+        Ok(Some(Box::new(SqsDeadLetterStore {
+            client: aws_sdk_sqs::Client::new(config),
+            queue_url,
+        })))
+    } else if let Ok(uri) = std::env::var("DEAD_LETTER_S3_URI") {
+        let location = S3Uri::prefix(&uri)
+            .map_err(|e| format!("Invalid DEAD_LETTER_S3_URI '{}': {}", uri, e))?;
+        Ok(Some(Box::new(S3DeadLetterStore {
+            client: aws_sdk_s3::Client::new(config),
+            bucket: location.bucket,
+            prefix: location.key,
+        })))
+    } else if let Ok(dir) = std::env::var("DEAD_LETTER_DIR") {
+        Ok(Some(Box::new(FileDeadLetterStore::new(dir))))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Failed deliveries as messages of an SQS queue. The handle is the receipt handle.
+pub struct SqsDeadLetterStore {
+    client: aws_sdk_sqs::Client,
+    queue_url: String,
+}
+
+#[async_trait]
+impl DeadLetterStore for SqsDeadLetterStore {
+    async fn put(&self, delivery: &FailedDelivery) -> Result<(), Error> {
+        let mut request = self.client
+            .send_message()
+            .queue_url(&self.queue_url)
+            .message_body(serde_json::to_string(delivery)?);
+        // FIFO queues drop duplicates of the same failure themselves
+        if self.queue_url.ends_with(".fifo") {
+            request = request
+                .message_group_id(&delivery.state.uid)
+                .message_deduplication_id(&delivery.id);
+        }
+        request.send().await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error> {
+        let mut letters = Vec::new();
+        let mut received = std::collections::HashSet::new();
+
+        // Received messages stay invisible for the visibility timeout, so every receive
+        // returns new ones until the queue is drained
+        loop {
+            let output = self.client
+                .receive_message()
+                .queue_url(&self.queue_url)
+                .max_number_of_messages(SQS_MAX_MESSAGES)
+                .wait_time_seconds(SQS_WAIT_SECONDS)
+                .visibility_timeout(SQS_VISIBILITY_SECONDS)
+                .send()
+                .await?;
+            if output.messages().is_empty() {
+                break;
+            }
+
+            for message in output.messages() {
+                let (Some(handle), Some(body)) = (message.receipt_handle(), message.body()) else {
+                    continue;
+                };
+                // A message received twice is replayed once
+                if let Some(id) = message.message_id()
+                    && !received.insert(id.to_string())
+                {
+                    continue;
+                }
+                match serde_json::from_str(body) {
+                    Ok(delivery) => letters.push(DeadLetter { handle: handle.to_string(), delivery }),
+                    Err(e) => tracing::error!("Skipping unreadable dead letter {:?}: {}", message.message_id(), e),
+                }
+            }
+        }
+
+        Ok(letters)
+    }
+
+    async fn delete(&self, letter: &DeadLetter) -> Result<(), Error> {
+        self.client
+            .delete_message()
+            .queue_url(&self.queue_url)
+            .receipt_handle(&letter.handle)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Failed deliveries as `<prefix>/<id>.json` objects. The handle is the object key.
+pub struct S3DeadLetterStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: String,
+}
+
+impl S3DeadLetterStore {
+    fn key(&self, id: &str) -> String {
+        if self.prefix.is_empty() {
+            format!("{}.json", id)
+        } else {
+            format!("{}/{}.json", self.prefix, id)
+        }
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for S3DeadLetterStore {
+    async fn put(&self, delivery: &FailedDelivery) -> Result<(), Error> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(self.key(&delivery.id))
+            .content_type("application/json")
+            .body(serde_json::to_vec(delivery)?.into())
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error> {
+        let mut letters = Vec::new();
+        let prefix = if self.prefix.is_empty() { String::new() } else { format!("{}/", self.prefix) };
+
+        let mut pages = self.client
+            .list_objects_v2()
+            .bucket(&self.bucket)
+            .prefix(prefix)
+            .into_paginator()
+            .send();
+        while let Some(page) = pages.next().await {
+            for object in page?.contents() {
+                let Some(key) = object.key().filter(|key| key.ends_with(".json")) else {
+                    continue;
+                };
+                // Letters deleted since the listing were replayed by another invocation
+                let Some(bytes) = s3::get_object(&self.client, &self.bucket, key).await? else {
+                    continue;
+                };
+                match serde_json::from_slice(&bytes) {
+                    Ok(delivery) => letters.push(DeadLetter { handle: key.to_string(), delivery }),
+                    Err(e) => tracing::error!("Skipping unreadable dead letter {}: {}", key, e),
+                }
+            }
+        }
+
+        Ok(letters)
+    }
+
+    async fn delete(&self, letter: &DeadLetter) -> Result<(), Error> {
+        self.client
+            .delete_object()
+            .bucket(&self.bucket)
+            .key(&letter.handle)
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+/// Failed deliveries as `<id>.json` files in a directory. The handle is the file path.
+pub struct FileDeadLetterStore {
+    dir: PathBuf,
+}
+
+impl FileDeadLetterStore {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl DeadLetterStore for FileDeadLetterStore {
+    async fn put(&self, delivery: &FailedDelivery) -> Result<(), Error> {
+        std::fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create dead letter directory '{}': {}", self.dir.display(), e))?;
+        let path = self.dir.join(format!("{}.json", delivery.id));
+        std::fs::write(&path, serde_json::to_string_pretty(delivery)?)
+            .map_err(|e| format!("Failed to write dead letter '{}': {}", path.display(), e).into())
+    }
+
+    async fn list(&self) -> Result<Vec<DeadLetter>, Error> {
+        let entries = match std::fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(format!("Failed to read dead letter directory '{}': {}", self.dir.display(), e).into()),
+        };
+
+        let mut letters = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let delivery = std::fs::read_to_string(&path)
+                .map_err(Error::from)
+                .and_then(|body| serde_json::from_str(&body).map_err(Error::from));
+            match delivery {
+                Ok(delivery) => letters.push(DeadLetter { handle: path.display().to_string(), delivery }),
+                Err(e) => tracing::error!("Skipping unreadable dead letter {}: {}", path.display(), e),
+            }
+        }
+
+        Ok(letters)
+    }
+
+    async fn delete(&self, letter: &DeadLetter) -> Result<(), Error> {
+        match std::fs::remove_file(&letter.handle) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Failed to delete dead letter '{}': {}", letter.handle, e).into()),
+        }
+    }
+}
+
+/// Outcome of a replay run.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReplayReport {
+    pub delivered: usize,
+    /// Letters dropped because a newer letter for the same finding and sink exists.
+    pub superseded: usize,
+    pub failed: usize,
+}
+
+/// Delivers the stored failed deliveries again and deletes the ones that succeed.
+///
+/// Only the latest letter per finding and sink is sent, so a finding that failed several
+/// times is posted once. Delivered findings are recorded in the state store, keeping the
+/// posted messages available for later status updates.
+pub async fn replay(
+    store: &dyn DeadLetterStore,
+    notifiers: &[Box<dyn Notifier>],
+    state_store: Option<&dyn FindingStateStore>,
+) -> Result<ReplayReport, Error> {
+    let mut report = ReplayReport::default();
+
+    // Keep the newest letter of each finding and sink
+    let mut latest: HashMap<(String, String), DeadLetter> = HashMap::new();
+    for letter in store.list().await? {
+        let key = (letter.delivery.notifier.clone(), letter.delivery.state.uid.clone());
+        let superseded = match latest.remove(&key) {
+            Some(current) if current.delivery.failed_at > letter.delivery.failed_at => {
+                latest.insert(key, current);
+                letter
+            }
+            Some(current) => {
+                latest.insert(key, letter);
+                current
+            }
+            None => {
+                latest.insert(key, letter);
+                continue;
+            }
+        };
+        report.superseded += 1;
+        if let Err(e) = store.delete(&superseded).await {
+            tracing::error!("Failed to delete superseded dead letter {}: {}", superseded.delivery.id, e);
+        }
+    }
+
+    let mut letters: Vec<DeadLetter> = latest.into_values().collect();
+    letters.sort_by_key(|letter| letter.delivery.failed_at);
+
+    for letter in letters {
+        let delivery = &letter.delivery;
+        let Some(notifier) = notifiers.iter().find(|n| n.name() == delivery.notifier) else {
+            tracing::error!("Cannot replay dead letter {}: notifier '{}' is not enabled", delivery.id, delivery.notifier);
+            report.failed += 1;
+            continue;
+        };
+
+        let result = match &delivery.status_change {
+            Some((previous_status, messages)) => {
+                notifier.notify_status_change(&delivery.summary, previous_status, messages).await
+            }
+            None if !delivery.channels.is_empty() => {
+                notifier.notify_channels(&delivery.summary, &delivery.channels).await
+            }
+            None => notifier.notify(&delivery.summary).await,
+        };
+
+        let messages = match result {
+            Ok(messages) => messages,
+            Err(e) => {
+                tracing::error!("Failed to replay finding {} to {}: {}", delivery.state.uid, delivery.notifier, e);
+                report.failed += 1;
+                continue;
+            }
+        };
+        report.delivered += 1;
+
+        if let Some(state_store) = state_store
+            && let Err(e) = record_state(state_store, &delivery.state, messages).await
+        {
+            tracing::error!("Failed to save state for finding {}: {}", delivery.state.uid, e);
+        }
+
+        if let Err(e) = store.delete(&letter).await {
+            tracing::error!("Failed to delete replayed dead letter {}: {}", delivery.id, e);
+        }
+    }
+
+    Ok(report)
+}
+
+/// Adds the replayed messages to the finding state, unless a newer notification of the
+/// finding has been recorded since.
+async fn record_state(
+    store: &dyn FindingStateStore,
+    state: &FindingState,
+    messages: Vec<MessageRef>,
+) -> Result<(), Error> {
+    let mut state = match store.get(&state.uid).await? {
+        Some(current) if current.last_notified > state.last_notified => return Ok(()),
+        Some(current) if current.last_notified == state.last_notified => current,
+        _ => state.clone(),
+    };
+    for message in messages {
+        if !state.messages.contains(&message) {
+            state.messages.push(message);
+        }
+    }
+    store.put(&state).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dedup::DedupPolicy;
+    use crate::state_store::MemoryStateStore;
+    use crate::struct_event::test_support;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Mutex;
+
+    /// Sink that fails while `failing` is set and records the findings it delivered.
+    #[derive(Default)]
+    struct FlakyNotifier {
+        failing: std::sync::atomic::AtomicBool,
+        delivered: Mutex<Vec<String>>,
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Notifier for FlakyNotifier {
+        fn name(&self) -> &'static str {
+            "slack"
+        }
+
+        async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                return Err("channel_not_found".into());
+            }
+            self.delivered.lock().unwrap().push(summary.uid.clone());
+            Ok(vec![MessageRef {
+                sink: "slack".to_string(),
+                channel: "C1".to_string(),
+                id: "1700000000.000100".to_string(),
+            }])
+        }
+    }
+
+    fn finding() -> Finding {
+        test_support::finding(include_str!("../fixtures/guardduty_prod_critical.json"))
+    }
+
+    fn failed_delivery(notifier: &dyn Notifier, failed_at: i64) -> FailedDelivery {
+        let finding = finding();
+        let summary = FindingSummary::from_finding(&finding);
+        let state = DedupPolicy::default().state_for(&summary, failed_at);
+        FailedDelivery::new(notifier, &"timeout".into(), failed_at, &finding, &summary, &state, None)
+    }
+
+    fn temp_store(name: &str) -> FileDeadLetterStore {
+        let dir = std::env::temp_dir().join(format!("dead-letters-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        FileDeadLetterStore::new(dir)
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_keeps_payload_and_finding() {
+        let notifier = FlakyNotifier::default();
+        let delivery = failed_delivery(&notifier, 1_700_000_000);
+
+        assert_eq!(delivery.notifier, "slack");
+        assert_eq!(delivery.error, "timeout");
+        assert_eq!(delivery.payload["uid"], delivery.summary.uid);
+        assert_eq!(delivery.finding.finding_info.as_ref().unwrap().uid.as_deref(), Some(delivery.summary.uid.as_str()));
+        // The id only depends on the sink and the notified state
+        assert_eq!(delivery.id, failed_delivery(&notifier, 1_700_000_000).id);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let store = temp_store("round-trip");
+        let delivery = failed_delivery(&FlakyNotifier::default(), 1_700_000_000);
+
+        store.put(&delivery).await.unwrap();
+        store.put(&delivery).await.unwrap();
+        let letters = store.list().await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].delivery.id, delivery.id);
+
+        store.delete(&letters[0]).await.unwrap();
+        assert!(store.list().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_replay_delivers_latest_letter_once() {
+        let store = temp_store("replay");
+        let flaky = FlakyNotifier::default();
+        let mut older = failed_delivery(&flaky, 1_700_000_000);
+        older.id = "older".to_string();
+        store.put(&older).await.unwrap();
+        store.put(&failed_delivery(&flaky, 1_700_000_600)).await.unwrap();
+
+        let notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(FlakyNotifier::default())];
+        let state_store = MemoryStateStore::default();
+
+        let report = replay(&store, &notifiers, Some(&state_store)).await.unwrap();
+        assert_eq!(report, ReplayReport { delivered: 1, superseded: 1, failed: 0 });
+        assert!(store.list().await.unwrap().is_empty());
+
+        let state = state_store.get(&older.state.uid).await.unwrap().unwrap();
+        assert_eq!(state.last_notified, 1_700_000_600);
+        assert_eq!(state.messages.len(), 1);
+
+        // Replaying again sends nothing
+        let report = replay(&store, &notifiers, Some(&state_store)).await.unwrap();
+        assert_eq!(report, ReplayReport::default());
+    }
+
+    #[tokio::test]
+    async fn test_failed_replay_keeps_letter() {
+        let store = temp_store("failed-replay");
+        let flaky = FlakyNotifier::default();
+        flaky.failing.store(true, Ordering::SeqCst);
+        store.put(&failed_delivery(&flaky, 1_700_000_000)).await.unwrap();
+
+        let notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(flaky)];
+        let report = replay(&store, &notifiers, None).await.unwrap();
+
+        assert_eq!(report.failed, 1);
+        assert_eq!(store.list().await.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_file_store_skips_unreadable_letters() {
+        let store = temp_store("unreadable");
+        let delivery = failed_delivery(&FlakyNotifier::default(), 1_700_000_000);
+        store.put(&delivery).await.unwrap();
+        std::fs::write(store.dir.join("truncated.json"), "{\"id\": ").unwrap();
+
+        let letters = store.list().await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].delivery.id, delivery.id);
+    }
+}
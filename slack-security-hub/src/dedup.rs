@@ -0,0 +1,267 @@
+use lambda_runtime::Error;
+use sha2::{Digest, Sha256};
+use std::str::FromStr;
+use crate::severity::Severity;
+use crate::state_store::FindingState;
+use crate::struct_event::FindingSummary;
+
+/// Summary fields that can be part of the deduplication fingerprint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FingerprintField {
+    Title,
+    Description,
+    ResourceId,
+    ResourceTypes,
+    Product,
+    Account,
+    Region,
+    Remediation,
+}
+
+impl FromStr for FingerprintField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "title" => Ok(FingerprintField::Title),
+            "description" => Ok(FingerprintField::Description),
+            "resource_id" => Ok(FingerprintField::ResourceId),
+            "resource_types" => Ok(FingerprintField::ResourceTypes),
+            "product" => Ok(FingerprintField::Product),
+            "account" => Ok(FingerprintField::Account),
+            "region" => Ok(FingerprintField::Region),
+            "remediation" => Ok(FingerprintField::Remediation),
+            other => Err(format!("Unknown fingerprint field: {}", other)),
+        }
+    }
+}
+
+/// Why a finding is (re-)posted.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotifyReason {
+    New,
+    SeverityChanged { from: Severity },
+    StatusChanged { from: String },
+    FingerprintChanged,
+    RenotifyIntervalElapsed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedupDecision {
+    Notify(NotifyReason),
+    Skip,
+}
+
+/// Decides whether a re-emitted finding is posted again.
+#[derive(Debug, Clone)]
+pub struct DedupPolicy {
+    pub fingerprint_fields: Vec<FingerprintField>,
+    /// Seconds after which an unchanged finding is posted again.
+    pub renotify_interval: Option<i64>,
+}
+
+impl Default for DedupPolicy {
+    fn default() -> Self {
+        Self {
+            fingerprint_fields: vec![FingerprintField::Title, FingerprintField::ResourceId],
+            renotify_interval: None,
+        }
+    }
+}
+
+impl DedupPolicy {
+    /// Reads `DEDUP_FINGERPRINT_FIELDS` (comma-separated) and `DEDUP_RENOTIFY_HOURS`.
+    pub fn from_env() -> Result<Self, Error> {
+        let mut policy = Self::default();
+
+        if let Ok(fields) = std::env::var("DEDUP_FINGERPRINT_FIELDS") {
+            policy.fingerprint_fields = fields
+                .split(',')
+                .filter(|f| !f.trim().is_empty())
+                .map(str::parse)
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Invalid DEDUP_FINGERPRINT_FIELDS: {}", e))?;
+        }
+
+        if let Ok(hours) = std::env::var("DEDUP_RENOTIFY_HOURS") {
+            let hours: u32 = hours.trim().parse()
+                .map_err(|e| format!("Invalid DEDUP_RENOTIFY_HOURS '{}': {}", hours, e))?;
+            policy.renotify_interval = Some(i64::from(hours) * 3600);
+        }
+
+        Ok(policy)
+    }
+
+    pub fn fingerprint(&self, summary: &FindingSummary) -> String {
+        let mut hasher = Sha256::new();
+        for field in &self.fingerprint_fields {
+            let value = match field {
+                FingerprintField::Title => summary.title.clone(),
+                FingerprintField::Description => summary.description.clone(),
+                FingerprintField::ResourceId => summary.resource_id.clone(),
+                FingerprintField::ResourceTypes => summary.resource_types.join(","),
+                FingerprintField::Product => summary.product_name.clone(),
+                FingerprintField::Account => summary.account.clone(),
+                FingerprintField::Region => summary.region.clone(),
+                FingerprintField::Remediation => summary.remediation.clone(),
+            };
+            hasher.update(value.as_bytes());
+            hasher.update([0u8]);
+        }
+        hex::encode(hasher.finalize())
+    }
+
+    /// Builds the state recorded once the finding has been posted at `now`.
+    pub fn state_for(&self, summary: &FindingSummary, now: i64) -> FindingState {
+        FindingState {
+            uid: summary.uid.clone(),
+            severity: summary.severity,
+            status: summary.status.clone(),
+            fingerprint: self.fingerprint(summary),
+            last_notified: now,
+            messages: Vec::new(),
+        }
+    }
+
+    pub fn decide(&self, previous: Option<&FindingState>, current: &FindingState) -> DedupDecision {
+        let Some(previous) = previous else {
+            return DedupDecision::Notify(NotifyReason::New);
+        };
+
+        // Status changes come first: they update the posted messages, which also picks up
+        // a severity change made at the same time
+        if !previous.status.eq_ignore_ascii_case(&current.status) {
+            DedupDecision::Notify(NotifyReason::StatusChanged { from: previous.status.clone() })
+        } else if previous.severity != current.severity {
+            DedupDecision::Notify(NotifyReason::SeverityChanged { from: previous.severity })
+        } else if previous.fingerprint != current.fingerprint {
+            DedupDecision::Notify(NotifyReason::FingerprintChanged)
+        } else if self.renotify_interval
+            .is_some_and(|interval| current.last_notified - previous.last_notified >= interval)
+        {
+            DedupDecision::Notify(NotifyReason::RenotifyIntervalElapsed)
+        } else {
+            DedupDecision::Skip
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state_store::{FindingStateStore, MemoryStateStore};
+    use crate::struct_event::test_support;
+
+    const NOW: i64 = 1_772_460_312;
+
+    fn summary() -> FindingSummary {
+        test_support::summary(include_str!("../fixtures/guardduty_prod_critical.json"))
+    }
+
+    #[test]
+    fn test_first_occurrence_is_new() {
+        let policy = DedupPolicy::default();
+        let current = policy.state_for(&summary(), NOW);
+        assert_eq!(policy.decide(None, &current), DedupDecision::Notify(NotifyReason::New));
+    }
+
+    #[tokio::test]
+    async fn test_re_emitted_finding_is_skipped() {
+        let policy = DedupPolicy::default();
+        let store = MemoryStateStore::default();
+        let mut summary = summary();
+        store.put(&policy.state_for(&summary, NOW)).await.unwrap();
+
+        // Only last_seen changes when Security Hub re-emits the finding
+        summary.last_seen = "2026-03-02 15:00:00 UTC".to_string();
+        let current = policy.state_for(&summary, NOW + 600);
+        let previous = store.get(&summary.uid).await.unwrap();
+
+        assert_eq!(policy.decide(previous.as_ref(), &current), DedupDecision::Skip);
+    }
+
+    #[test]
+    fn test_severity_and_status_changes_notify() {
+        let policy = DedupPolicy::default();
+        let mut summary = summary();
+        let previous = policy.state_for(&summary, NOW);
+
+        summary.severity = Severity::High;
+        assert_eq!(
+            policy.decide(Some(&previous), &policy.state_for(&summary, NOW)),
+            DedupDecision::Notify(NotifyReason::SeverityChanged { from: Severity::Critical })
+        );
+
+        summary.severity = Severity::Critical;
+        summary.status = "Resolved".to_string();
+        assert_eq!(
+            policy.decide(Some(&previous), &policy.state_for(&summary, NOW)),
+            DedupDecision::Notify(NotifyReason::StatusChanged { from: "New".to_string() })
+        );
+
+        // Changing both updates the original messages instead of posting a new one
+        summary.severity = Severity::High;
+        assert_eq!(
+            policy.decide(Some(&previous), &policy.state_for(&summary, NOW)),
+            DedupDecision::Notify(NotifyReason::StatusChanged { from: "New".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_fields_are_configurable() {
+        let mut summary = summary();
+        let policy = DedupPolicy::default();
+        let with_description = DedupPolicy {
+            fingerprint_fields: vec![FingerprintField::Title, FingerprintField::Description],
+            renotify_interval: None,
+        };
+        let previous = policy.state_for(&summary, NOW);
+        let previous_with_description = with_description.state_for(&summary, NOW);
+
+        summary.description = "Updated description".to_string();
+
+        assert_eq!(policy.decide(Some(&previous), &policy.state_for(&summary, NOW)), DedupDecision::Skip);
+        assert_eq!(
+            with_description.decide(Some(&previous_with_description), &with_description.state_for(&summary, NOW)),
+            DedupDecision::Notify(NotifyReason::FingerprintChanged)
+        );
+    }
+
+    #[test]
+    fn test_renotify_interval() {
+        let policy = DedupPolicy {
+            renotify_interval: Some(24 * 3600),
+            ..Default::default()
+        };
+        let summary = summary();
+        let previous = policy.state_for(&summary, NOW);
+
+        assert_eq!(policy.decide(Some(&previous), &policy.state_for(&summary, NOW + 3600)), DedupDecision::Skip);
+        assert_eq!(
+            policy.decide(Some(&previous), &policy.state_for(&summary, NOW + 24 * 3600)),
+            DedupDecision::Notify(NotifyReason::RenotifyIntervalElapsed)
+        );
+    }
+
+    #[test]
+    fn test_findings_without_uid_do_not_share_state() {
+        let mut finding = test_support::finding(include_str!("../fixtures/guardduty_prod_critical.json"));
+        finding.metadata.as_mut().unwrap().uid = None;
+        finding.finding_info.as_mut().unwrap().uid = None;
+
+        let first = FindingSummary::from_finding(&finding);
+        finding.finding_info.as_mut().unwrap().last_seen_time_dt = Some("2026-03-02T15:00:00Z".to_string());
+        let re_emitted = FindingSummary::from_finding(&finding);
+        finding.finding_info.as_mut().unwrap().title = Some("Another finding on the instance".to_string());
+        let other = FindingSummary::from_finding(&finding);
+
+        assert!(first.uid.starts_with("unknown-finding-"));
+        assert_eq!(first.uid, re_emitted.uid);
+        assert_ne!(first.uid, other.uid);
+    }
+
+    #[test]
+    fn test_invalid_fingerprint_field() {
+        assert!("title,colour".split(',').map(str::parse::<FingerprintField>).any(|f| f.is_err()));
+    }
+}
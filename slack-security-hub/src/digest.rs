@@ -0,0 +1,169 @@
+use lambda_runtime::Error;
+use std::cmp::Reverse;
+use serde_json::{json, Value};
+use crate::blockkit;
+use crate::severity::Severity;
+use crate::struct_event::FindingSummary;
+
+/// Batches above `threshold` new findings are posted as one digest message with the
+/// findings in its thread instead of one message per finding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestPolicy {
+    pub threshold: Option<usize>,
+    /// Number of findings listed in the digest message itself.
+    pub top_findings: usize,
+}
+
+impl Default for DigestPolicy {
+    fn default() -> Self {
+        Self {
+            threshold: None,
+            top_findings: 5,
+        }
+    }
+}
+
+impl DigestPolicy {
+    /// Reads `DIGEST_THRESHOLD` (digest mode is off when unset) and `DIGEST_TOP_FINDINGS`.
+    pub fn from_env() -> Result<Self, Error> {
+        let mut policy = Self::default();
+
+        if let Ok(threshold) = std::env::var("DIGEST_THRESHOLD") {
+            policy.threshold = Some(threshold.trim().parse()
+                .map_err(|e| format!("Invalid DIGEST_THRESHOLD '{}': {}", threshold, e))?);
+        }
+
+        if let Ok(top) = std::env::var("DIGEST_TOP_FINDINGS") {
+            policy.top_findings = top.trim().parse()
+                .map_err(|e| format!("Invalid DIGEST_TOP_FINDINGS '{}': {}", top, e))?;
+        }
+
+        Ok(policy)
+    }
+
+    pub fn applies(&self, findings: usize) -> bool {
+        self.threshold.is_some_and(|threshold| findings > threshold)
+    }
+}
+
+/// Counts of `(key, findings)` in order of first appearance.
+fn count_by<K: PartialEq>(summaries: &[&FindingSummary], key: impl Fn(&FindingSummary) -> K) -> Vec<(K, usize)> {
+    let mut counts: Vec<(K, usize)> = Vec::new();
+    for summary in summaries {
+        let key = key(summary);
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((key, 1)),
+        }
+    }
+    counts
+}
+
+/// Digest message grouping the findings by severity and product, followed by the most
+/// severe findings.
+pub fn digest_blocks(summaries: &[&FindingSummary], policy: &DigestPolicy) -> Vec<Value> {
+    let mut by_severity = count_by(summaries, |s| s.severity);
+    by_severity.sort_by_key(|(severity, _)| Reverse(*severity));
+    let severity_lines: Vec<String> = by_severity.iter()
+        .map(|(severity, count)| format!("{} *{}*: {}", severity_emoji(*severity), severity, count))
+        .collect();
+
+    let mut by_product = count_by(summaries, |s| s.product_name.clone());
+    by_product.sort_by_key(|(_, count)| Reverse(*count));
+    let product_lines: Vec<String> = by_product.iter()
+        .map(|(product, count)| format!("*{}*: {}", product, count))
+        .collect();
+
+    // Stable sort keeps the event order among findings of the same severity
+    let mut top: Vec<&&FindingSummary> = summaries.iter().collect();
+    top.sort_by_key(|s| Reverse(s.severity));
+    let top_lines: Vec<String> = top.iter()
+        .take(policy.top_findings)
+        .map(|s| format!("• *{}*  {}  _({} | {})_", s.severity, s.title, s.account, s.region))
+        .collect();
+
+    let mut blocks = vec![
+        blockkit::header(&format!("{} new Security Hub findings", summaries.len())),
+        json!({
+            "type": "section",
+            "fields": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*By severity*\n{}", severity_lines.join("\n"))
+                },
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*By product*\n{}", product_lines.join("\n"))
+                }
+            ]
+        }),
+    ];
+
+    if !top_lines.is_empty() {
+        blocks.extend(blockkit::mrkdwn_sections(
+            &format!("*Top findings*\n{}", top_lines.join("\n")),
+            None,
+        ));
+    }
+
+    blocks.push(json!({
+        "type": "context",
+        "elements": [
+            {
+                "type": "mrkdwn",
+                "text": "Full details of every finding are in the thread."
+            }
+        ]
+    }));
+
+    blockkit::enforce_limits(blocks)
+}
+
+pub(crate) fn severity_emoji(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Fatal | Severity::Critical => ":red_circle:",
+        Severity::High => ":large_orange_circle:",
+        Severity::Medium => ":large_yellow_circle:",
+        _ => ":white_circle:",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+
+    #[test]
+    fn test_digest_threshold() {
+        let policy = DigestPolicy { threshold: Some(10), ..Default::default() };
+
+        assert!(!policy.applies(10));
+        assert!(policy.applies(11));
+        assert!(!DigestPolicy::default().applies(500));
+    }
+
+    #[test]
+    fn test_digest_groups_by_severity_and_product() {
+        let inspector = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        let guardduty = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let summaries = vec![&inspector, &inspector, &guardduty, &inspector];
+        let policy = DigestPolicy { threshold: Some(3), top_findings: 2 };
+
+        let blocks = digest_blocks(&summaries, &policy);
+
+        assert_eq!(blocks[0]["text"]["text"], "4 new Security Hub findings");
+        assert_eq!(
+            blocks[1]["fields"][0]["text"],
+            "*By severity*\n:red_circle: *Critical*: 1\n:large_orange_circle: *High*: 3"
+        );
+        assert_eq!(blocks[1]["fields"][1]["text"], "*By product*\n*Inspector*: 3\n*GuardDuty*: 1");
+        assert_eq!(
+            blocks[2]["text"]["text"],
+            format!(
+                "*Top findings*\n• *Critical*  {}  _(111111111111 | us-east-1)_\n• *High*  {}  _(222222222222 | eu-west-1)_",
+                guardduty.title, inspector.title
+            )
+        );
+        assert_eq!(blocks[3]["type"], "context");
+    }
+}
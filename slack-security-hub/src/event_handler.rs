@@ -1,74 +1,481 @@
 use lambda_runtime::{tracing, Error, LambdaEvent};
 use aws_lambda_events::event::eventbridge::EventBridgeEvent;
-use aws_config::BehaviorVersion;
+use aws_config::{BehaviorVersion, SdkConfig};
 use aws_sdk_secretsmanager::Client as SMClient;
-use crate::struct_event::{FindingSummary, Detail, Finding};
-use crate::slack_client::post_slack_message;
+use crate::struct_event::{parse_findings, Finding, FindingSummary};
+use crate::routing::RoutingRules;
+use crate::notifier::{MessageRef, Notifier, NotifierKind, PartialDelivery, SlackNotifier};
+use crate::slack_client::SlackClient;
+use crate::teams::TeamsNotifier;
+use crate::webhook::WebhookNotifier;
+use crate::severity::SeverityFilter;
+use crate::dedup::{DedupDecision, DedupPolicy, NotifyReason};
+use crate::state_store::{self, FindingState};
+use crate::deadletter::{self, DeadLetterStore, FailedDelivery, REPLAY_DETAIL_TYPE};
+use crate::digest::DigestPolicy;
+use crate::correlation::{self, CorrelationPolicy, Incident, IncidentStore};
+use crate::suppression::SuppressionRules;
+use crate::accounts::AccountEnricher;
+use crate::mentions::OwnerMentions;
+use crate::risk::RiskModel;
+use crate::securityhub::{FindingIdentifier, SecurityHubClient};
+use aws_sdk_securityhub::types::WorkflowStatus;
+use crate::posture::{self, SecurityHubFindingsSource, SCHEDULED_EVENT_DETAIL_TYPE};
+use crate::pagerduty::{PagerDutyClient, DEFAULT_EVENTS_URL};
 use serde_json::Value;
+use std::time::SystemTime;
 
-pub(crate) async fn function_handler(event: LambdaEvent<EventBridgeEvent<Value>>) -> Result<(), Error> {
+pub async fn function_handler(event: LambdaEvent<EventBridgeEvent<Value>>) -> Result<(), Error> {
     let payload = event.payload;
+    // Slack calls are not retried past the deadline of the invocation
+    let deadline = event.context.deadline();
     let config = aws_config::defaults(BehaviorVersion::latest())
         .load()
         .await;
 
-    // Define the Slack channel from env
-    let channel = std::env::var("SLACK_CHANNEL")
-        .expect("SLACK_CHANNEL environment variable not set");
+    // EventBridge schedules trigger the posture report instead of finding notifications
+    if payload.detail_type == SCHEDULED_EVENT_DETAIL_TYPE {
+        return posture_report(&config, &SMClient::new(&config), deadline).await;
+    }
+
+    // Manual invocations with this detail type re-send the failed deliveries
+    if payload.detail_type == REPLAY_DETAIL_TYPE {
+        return replay_failed_deliveries(&config, deadline).await;
+    }
+
+    let severity_filter = SeverityFilter::from_env()?;
+
+    // Deduplication is enabled when a state store is configured
+    let state_store = state_store::from_env(&config);
+    let dedup_policy = DedupPolicy::from_env()?;
+    let digest_policy = DigestPolicy::from_env()?;
+    // Failed deliveries are kept for replay when a dead-letter store is configured
+    let dead_letters = deadletter::from_env(&config)?;
+    // Findings on the same resource are grouped into incidents when an incident store is configured
+    let incident_store = correlation::store_from_env(&config);
+    let correlation_policy = CorrelationPolicy::from_env()?;
+    let suppression_rules = SuppressionRules::from_env(&config).await?;
+    let account_enricher = AccountEnricher::from_env(&config)?;
+    let owner_mentions = OwnerMentions::from_env()?;
+    let risk_model = RiskModel::from_env()?;
 
-    // Retrieve the token from AWS Secrets Manager
+    // Build the configured notification sinks
     let secrets_client = SMClient::new(&config);
-    let secret_name = "slack-token";
-    let token = match get_secret(&secrets_client, secret_name).await {
-        Ok(token) => token,
-        Err(e) => {
-            let err_msg = format!("Failed to retrieve secret '{}': {}", secret_name, e);
-            tracing::error!("{}", err_msg);
-            return Err(err_msg.into());
+    let notifiers = build_notifiers(&secrets_client, deadline).await?;
+
+    // PagerDuty escalation is enabled when the routing key secret is configured
+    let pagerduty = match std::env::var("PAGERDUTY_SECRET_NAME") {
+        Ok(secret_name) => {
+            let routing_key = get_secret(&secrets_client, &secret_name, "routing_key").await
+                .map_err(|e| format!("Failed to retrieve secret '{}': {}", secret_name, e))?;
+            let events_url = std::env::var("PAGERDUTY_EVENTS_URL")
+                .unwrap_or_else(|_| DEFAULT_EVENTS_URL.to_string());
+            let min_risk_score = match std::env::var("PAGERDUTY_MIN_RISK_SCORE") {
+                Ok(value) => Some(value.parse::<u32>()
+                    .map_err(|e| format!("Invalid PAGERDUTY_MIN_RISK_SCORE '{}': {}", value, e))?),
+                Err(_) => None,
+            };
+            Some(PagerDutyClient::new(routing_key, events_url).with_min_risk_score(min_risk_score))
         }
+        Err(_) => None,
     };
 
-    // Parse the detail field into our custom Detail struct
-    let detail: Detail = serde_json::from_value(payload.detail.clone())
-        .map_err(|e| format!("Failed to parse detail: {}", e))?;
-   
-    let findings = detail.findings.as_ref()
-        .ok_or("Missing findings in detail")?;
+    // Parse the findings, normalizing legacy ASFF events into OCSF
+    let findings = parse_findings(&payload.detail_type, payload.detail)?;
 
-    for finding in findings {
-        let summary = FindingSummary::from_finding(finding);
+    let today = chrono::Utc::now().date_naive();
+    let mut suppressed = 0;
+    // Rule name, note and findings to set to SUPPRESSED in Security Hub
+    let mut suppress_in_security_hub: Vec<(String, String, Vec<FindingIdentifier>)> = Vec::new();
+
+    let mut pending = Vec::new();
+    for finding in &findings {
+        let mut summary = FindingSummary::from_finding(finding);
         tracing::info!("Processing finding: {}", summary.title);
 
-        if summary.severity == "High" || summary.severity == "Critical" {
-             tracing::warn!("High severity finding detected: {}", summary.title);
-             handle_high_severity_finding(finding).await?;
+        if !severity_filter.allows(summary.severity) {
+            tracing::info!("Skipping {} severity finding: {}", summary.severity, summary.title);
+            continue;
         }
 
-        // Post the finding summary to Slack
-        match post_slack_message(&token, &channel, summary).await {
-            Ok(_) => (),
-            Err(e) => tracing::error!("Failed to post finding to Slack: {}", e),
+        if let Some(rule) = suppression_rules.matching(&summary, today) {
+            tracing::info!(
+                "Suppressing finding {} by rule '{}' (expires {}): {}",
+                summary.uid, rule.name, rule.expires, rule.reason
+            );
+            suppressed += 1;
+            if rule.suppress_in_security_hub
+                && !summary.status.eq_ignore_ascii_case("Suppressed")
+                && let Some(finding) = summary.security_hub_id.clone()
+            {
+                // Batch the Security Hub update per rule, as they share the same note
+                match suppress_in_security_hub.iter_mut().find(|(name, _, _)| *name == rule.name) {
+                    Some((_, _, findings)) => findings.push(finding),
+                    None => suppress_in_security_hub.push((
+                        rule.name.clone(),
+                        format!("Suppressed by rule '{}': {}", rule.name, rule.reason),
+                        vec![finding],
+                    )),
+                }
+            }
+            continue;
+        }
+
+        let state = dedup_policy.state_for(&summary, chrono::Utc::now().timestamp());
+        let mut status_change = None;
+        // Without a state store every finding is treated as new
+        let mut reason = NotifyReason::New;
+        if let Some(store) = &state_store {
+            // Fail open: a state store error must not drop the notification
+            let previous_state = store.get(&summary.uid).await.unwrap_or_else(|e| {
+                tracing::error!("Failed to read state for finding {}: {}", summary.uid, e);
+                None
+            });
+
+            match dedup_policy.decide(previous_state.as_ref(), &state) {
+                DedupDecision::Skip => {
+                    tracing::info!("Skipping duplicate finding: {}", summary.uid);
+                    continue;
+                }
+                DedupDecision::Notify(notify_reason) => {
+                    tracing::info!("Notifying finding {} ({:?})", summary.uid, notify_reason);
+
+                    // Status changes update the original messages instead of posting new ones
+                    if let NotifyReason::StatusChanged { from } = &notify_reason
+                        && let Some(previous_state) = previous_state
+                    {
+                        status_change = Some((from.clone(), previous_state.messages));
+                    }
+                    reason = notify_reason;
+                }
+            }
+        }
+
+        if let Some(enricher) = &account_enricher {
+            summary.account_info = enricher.lookup(&summary.account).await;
+        }
+        summary.owners = owner_mentions.owners(&summary);
+        summary.risk = Some(risk_model.score(&summary));
+
+        if let Some(pagerduty) = &pagerduty
+            && let Err(e) = pagerduty.escalate(&summary, &reason).await
+        {
+            tracing::error!("Failed to escalate finding {} to PagerDuty: {}", summary.uid, e);
+        }
+
+        pending.push(PendingNotification { finding, summary, state, status_change, incomplete: false });
+    }
+
+    if suppressed > 0 {
+        tracing::info!("Suppressed {} of {} findings", suppressed, findings.len());
+    }
+
+    if !suppress_in_security_hub.is_empty() {
+        let security_hub = SecurityHubClient::new(&config);
+        for (rule, note, findings) in &suppress_in_security_hub {
+            if let Err(e) = security_hub.set_workflow_status(findings, WorkflowStatus::Suppressed, note).await {
+                tracing::error!("Failed to suppress findings of rule '{}' in Security Hub: {}", rule, e);
+            }
+        }
+    }
+
+    // Status changes always update their original messages
+    let (mut updates, mut new): (Vec<_>, Vec<_>) = pending.into_iter()
+        .partition(|p| p.status_change.is_some());
+
+    deliver_new(
+        &notifiers,
+        &mut new,
+        incident_store.as_deref(),
+        &correlation_policy,
+        &digest_policy,
+        dead_letters.as_deref(),
+    ).await;
+    for notification in &mut updates {
+        deliver(&notifiers, notification, dead_letters.as_deref()).await;
+    }
+
+    // Only record findings every sink delivered, or whose failures are kept for replay, so
+    // the others are delivered again on the next emission
+    if let Some(store) = &state_store {
+        for notification in new.iter().chain(&updates).filter(|n| !n.incomplete) {
+            if let Err(e) = store.put(&notification.state).await {
+                tracing::error!("Failed to save state for finding {}: {}", notification.summary.uid, e);
+            }
         }
     }
 
     Ok(())
 }
 
-pub async fn handle_high_severity_finding(_finding: &Finding) -> Result<(), Error> {
-    tracing::warn!("High severity finding detected!");
-    
-    // TODO: Implement your high-severity handling logic:
-    // - Send urgent notifications
-    // - Trigger automated response
-    // - Create high-priority tickets
-    // - Alert security team
-    
+/// A finding that passed the filters, with the state to record once it is delivered.
+struct PendingNotification<'a> {
+    finding: &'a Finding,
+    summary: FindingSummary,
+    state: FindingState,
+    /// Previous status and messages when the workflow status changed.
+    status_change: Option<(String, Vec<MessageRef>)>,
+    /// Set when a sink failed and the failure could not be kept for replay.
+    incomplete: bool,
+}
+
+/// Delivers new findings. Large batches are posted as a digest, so a rescan does not open
+/// an incident per resource; smaller ones go into the incident of their resource when an
+/// incident store is configured.
+async fn deliver_new(
+    notifiers: &[Box<dyn Notifier>],
+    new: &mut [PendingNotification<'_>],
+    incident_store: Option<&dyn IncidentStore>,
+    correlation_policy: &CorrelationPolicy,
+    digest_policy: &DigestPolicy,
+    dead_letters: Option<&dyn DeadLetterStore>,
+) {
+    if digest_policy.applies(new.len()) {
+        tracing::info!("Posting {} new findings as a digest", new.len());
+        deliver_digest(notifiers, new, digest_policy, dead_letters).await;
+    } else if let Some(store) = incident_store {
+        deliver_correlated(notifiers, new, store, correlation_policy, dead_letters).await;
+    } else {
+        for notification in new {
+            deliver(notifiers, notification, dead_letters).await;
+        }
+    }
+}
+
+async fn deliver(
+    notifiers: &[Box<dyn Notifier>],
+    notification: &mut PendingNotification<'_>,
+    dead_letters: Option<&dyn DeadLetterStore>,
+) {
+    for notifier in notifiers {
+        let result = match &notification.status_change {
+            Some((previous_status, messages)) => {
+                notifier.notify_status_change(&notification.summary, previous_status, messages).await
+            }
+            None => notifier.notify(&notification.summary).await,
+        };
+
+        record_result(notifier.as_ref(), notification, result, dead_letters).await;
+    }
+}
+
+async fn deliver_digest(
+    notifiers: &[Box<dyn Notifier>],
+    notifications: &mut [PendingNotification<'_>],
+    policy: &DigestPolicy,
+    dead_letters: Option<&dyn DeadLetterStore>,
+) {
+    for notifier in notifiers {
+        let summaries: Vec<&FindingSummary> = notifications.iter().map(|n| &n.summary).collect();
+        let results = notifier.notify_digest(&summaries, policy).await;
+
+        for (notification, result) in notifications.iter_mut().zip(results) {
+            record_result(notifier.as_ref(), notification, result, dead_letters).await;
+        }
+    }
+}
+
+/// Delivers findings into the open incident of their resource, opening one when there is
+/// none within the correlation window, and saves the incidents afterwards. Findings without
+/// a resource are delivered on their own.
+async fn deliver_correlated(
+    notifiers: &[Box<dyn Notifier>],
+    notifications: &mut [PendingNotification<'_>],
+    store: &dyn IncidentStore,
+    policy: &CorrelationPolicy,
+    dead_letters: Option<&dyn DeadLetterStore>,
+) {
+    let now = chrono::Utc::now().timestamp();
+    // Incidents touched by this event, so findings on one resource share their incident
+    let mut incidents: Vec<Incident> = Vec::new();
+
+    for notification in notifications.iter_mut() {
+        let Some(resource_uid) = correlation::resource_key(&notification.summary) else {
+            deliver(notifiers, notification, dead_letters).await;
+            continue;
+        };
+
+        let index = match incidents.iter().position(|i| i.resource_uid == resource_uid) {
+            Some(index) => index,
+            None => {
+                // Fail open: a store error opens a new incident instead of dropping the finding
+                let open = store.get(resource_uid).await
+                    .unwrap_or_else(|e| {
+                        tracing::error!("Failed to read incident of resource {}: {}", resource_uid, e);
+                        None
+                    })
+                    .filter(|incident| policy.is_open(incident, now));
+                incidents.push(open.unwrap_or_else(|| Incident::open(&notification.summary, now)));
+                incidents.len() - 1
+            }
+        };
+        let incident = &mut incidents[index];
+        incident.add(&notification.summary, now);
+        tracing::info!(
+            "Correlating finding {} into the incident of {} ({} findings)",
+            notification.summary.uid, incident.resource_uid, incident.findings.len()
+        );
+
+        for notifier in notifiers {
+            let result = notifier.notify_incident(&notification.summary, incident).await;
+            record_result(notifier.as_ref(), notification, result, dead_letters).await;
+        }
+    }
+
+    for incident in &incidents {
+        if let Err(e) = store.put(incident).await {
+            tracing::error!("Failed to save incident of resource {}: {}", incident.resource_uid, e);
+        }
+    }
+}
+
+/// Adds the messages a sink posted to the state of the finding. Messages posted before a
+/// partial failure are kept too, and only the failed channels are kept for replay.
+async fn record_result(
+    notifier: &dyn Notifier,
+    notification: &mut PendingNotification<'_>,
+    result: Result<Vec<MessageRef>, Error>,
+    dead_letters: Option<&dyn DeadLetterStore>,
+) {
+    let error = match result {
+        Ok(messages) => {
+            notification.state.messages.extend(messages);
+            return;
+        }
+        Err(error) => error,
+    };
+
+    let failed_channels = match error.downcast_ref::<PartialDelivery>() {
+        Some(partial) => {
+            notification.state.messages.extend(partial.delivered.iter().cloned());
+            partial.failed.clone()
+        }
+        None => Vec::new(),
+    };
+    notification.incomplete |= !record_failure(notifier, notification, error, &failed_channels, dead_letters).await;
+}
+
+/// Logs a failed delivery and keeps it in the dead-letter store for replay. Returns whether
+/// the delivery was kept.
+async fn record_failure(
+    notifier: &dyn Notifier,
+    notification: &PendingNotification<'_>,
+    error: Error,
+    failed_channels: &[String],
+    dead_letters: Option<&dyn DeadLetterStore>,
+) -> bool {
+    tracing::error!("Failed to deliver finding {} to {}: {}", notification.summary.uid, notifier.name(), error);
+
+    let Some(store) = dead_letters else {
+        return false;
+    };
+    let delivery = FailedDelivery::new(
+        notifier,
+        &error,
+        chrono::Utc::now().timestamp(),
+        notification.finding,
+        &notification.summary,
+        &notification.state,
+        notification.status_change.as_ref(),
+    ).with_channels(failed_channels);
+    match store.put(&delivery).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::error!("Failed to store failed delivery of finding {}: {}", notification.summary.uid, e);
+            false
+        }
+    }
+}
+
+/// Re-sends the deliveries kept in the dead-letter store.
+async fn replay_failed_deliveries(config: &SdkConfig, deadline: SystemTime) -> Result<(), Error> {
+    let store = deadletter::from_env(config)?
+        .ok_or("No dead-letter store configured")?;
+    let notifiers = build_notifiers(&SMClient::new(config), deadline).await?;
+    let state_store = state_store::from_env(config);
+
+    let report = deadletter::replay(store.as_ref(), &notifiers, state_store.as_deref()).await?;
+    tracing::info!(
+        "Replayed failed deliveries: {} delivered, {} superseded, {} failed",
+        report.delivered, report.superseded, report.failed
+    );
+
+    if report.failed > 0 {
+        return Err(format!("{} failed deliveries could not be replayed", report.failed).into());
+    }
     Ok(())
 }
 
+async fn build_notifiers(secrets_client: &SMClient, deadline: SystemTime) -> Result<Vec<Box<dyn Notifier>>, Error> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+
+    for kind in NotifierKind::from_env()? {
+        match kind {
+            NotifierKind::Slack => {
+                // Load the channel routing rules (falls back to SLACK_CHANNEL)
+                let routing = RoutingRules::from_env()?;
+
+                notifiers.push(Box::new(SlackNotifier::new(slack_client(secrets_client, deadline).await?, routing)));
+            }
+            NotifierKind::Teams => {
+                let webhook_url = std::env::var("TEAMS_WEBHOOK_URL")
+                    .map_err(|_| "TEAMS_WEBHOOK_URL environment variable not set")?;
+                notifiers.push(Box::new(TeamsNotifier::new(webhook_url)));
+            }
+            NotifierKind::Webhook => {
+                let url = std::env::var("WEBHOOK_URL")
+                    .map_err(|_| "WEBHOOK_URL environment variable not set")?;
+                let signing_secret = match std::env::var("WEBHOOK_SECRET_NAME") {
+                    Ok(secret_name) => Some(
+                        get_secret(secrets_client, &secret_name, "secret").await
+                            .map_err(|e| format!("Failed to retrieve secret '{}': {}", secret_name, e))?,
+                    ),
+                    Err(_) => None,
+                };
+                notifiers.push(Box::new(WebhookNotifier::new(url, signing_secret)));
+            }
+        }
+    }
+
+    Ok(notifiers)
+}
+
+/// Creates the Slack client with the bot token from AWS Secrets Manager.
+async fn slack_client(secrets_client: &SMClient, deadline: SystemTime) -> Result<SlackClient, Error> {
+    let secret_name = "slack-token";
+    let token = match get_secret(secrets_client, secret_name, "token").await {
+        Ok(token) => token,
+        Err(e) => {
+            let err_msg = format!("Failed to retrieve secret '{}': {}", secret_name, e);
+            tracing::error!("{}", err_msg);
+            return Err(err_msg.into());
+        }
+    };
+    Ok(SlackClient::from_env(token)?.with_deadline(deadline))
+}
+
+/// Posts the posture report of the active Security Hub findings to `POSTURE_CHANNEL`
+/// (falls back to `SLACK_CHANNEL`).
+async fn posture_report(config: &SdkConfig, secrets_client: &SMClient, deadline: SystemTime) -> Result<(), Error> {
+    let channel = std::env::var("POSTURE_CHANNEL")
+        .or_else(|_| std::env::var("SLACK_CHANNEL"))
+        .map_err(|_| "POSTURE_CHANNEL environment variable not set")?;
+    let slack = slack_client(secrets_client, deadline).await?;
+    let snapshots = posture::snapshot_store_from_env(config)?;
+
+    posture::run_report(
+        &SecurityHubFindingsSource::new(config),
+        snapshots.as_deref(),
+        &slack,
+        &channel,
+        chrono::Utc::now(),
+    ).await
+}
+
 async fn get_secret(
     client: &SMClient,
     secret_name: &str,
+    field: &str,
 ) -> Result<String, Error> {
     let response = client
         .get_secret_value()
@@ -78,12 +485,12 @@ async fn get_secret(
 
     // Handle both string and JSON secrets
     let secret = if let Some(secret_string) = response.secret_string() {
-        // If the secret is a JSON object with a "token" field
+        // If the secret is a JSON object, read the requested field
         if secret_string.starts_with('{') {
             let json: Value = serde_json::from_str(secret_string)?;
-            json["token"]
+            json[field]
                 .as_str()
-                .ok_or("Token field not found in secret")?
+                .ok_or_else(|| format!("Field '{}' not found in secret", field))?
                 .to_string()
         } else {
             // Plain string secret
@@ -94,4 +501,148 @@ async fn get_secret(
     };
 
     Ok(secret)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::correlation::MemoryIncidentStore;
+    use crate::dedup::DedupPolicy;
+    use crate::struct_event::test_support;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    /// Sink that records how each finding was delivered.
+    struct RecordingNotifier {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[async_trait]
+    impl Notifier for RecordingNotifier {
+        fn name(&self) -> &'static str {
+            "slack"
+        }
+
+        async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+            self.calls.lock().unwrap().push(format!("notify {}", summary.product_aws));
+            Ok(Vec::new())
+        }
+
+        async fn notify_incident(
+            &self,
+            summary: &FindingSummary,
+            _incident: &mut Incident,
+        ) -> Result<Vec<MessageRef>, Error> {
+            self.calls.lock().unwrap().push(format!("incident {}", summary.product_aws));
+            Ok(Vec::new())
+        }
+
+        async fn notify_digest(
+            &self,
+            summaries: &[&FindingSummary],
+            _policy: &DigestPolicy,
+        ) -> Vec<Result<Vec<MessageRef>, Error>> {
+            self.calls.lock().unwrap().push(format!("digest {}", summaries.len()));
+            summaries.iter().map(|_| Ok(Vec::new())).collect()
+        }
+    }
+
+    fn findings() -> Vec<Finding> {
+        [
+            include_str!("../fixtures/guardduty_prod_critical.json"),
+            include_str!("../fixtures/inspector_dev_high.json"),
+            include_str!("../fixtures/macie_sensitive_data.json"),
+        ]
+        .iter()
+        .map(|fixture| test_support::finding(fixture))
+        .collect()
+    }
+
+    fn pending(findings: &[Finding]) -> Vec<PendingNotification<'_>> {
+        findings.iter()
+            .map(|finding| {
+                let summary = FindingSummary::from_finding(finding);
+                let state = DedupPolicy::default().state_for(&summary, 1_700_000_000);
+                PendingNotification { finding, summary, state, status_change: None, incomplete: false }
+            })
+            .collect()
+    }
+
+    fn findings_resource(index: usize) -> String {
+        let summary = FindingSummary::from_finding(&findings()[index]);
+        correlation::resource_key(&summary).unwrap().to_string()
+    }
+
+    async fn deliver_with_threshold(threshold: Option<usize>) -> (Vec<String>, MemoryIncidentStore) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let notifiers: Vec<Box<dyn Notifier>> = vec![Box::new(RecordingNotifier { calls: calls.clone() })];
+        let incidents = MemoryIncidentStore::default();
+        let digest_policy = DigestPolicy { threshold, ..Default::default() };
+
+        let findings = findings();
+        let mut new = pending(&findings);
+        deliver_new(&notifiers, &mut new, Some(&incidents), &CorrelationPolicy::default(), &digest_policy, None).await;
+
+        let calls = calls.lock().unwrap().clone();
+        (calls, incidents)
+    }
+
+    #[tokio::test]
+    async fn test_large_batch_is_posted_as_digest_with_correlation_enabled() {
+        let (calls, incidents) = deliver_with_threshold(Some(2)).await;
+
+        assert_eq!(calls, vec!["digest 3"]);
+        assert!(incidents.get(&findings_resource(0)).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_small_batch_is_correlated() {
+        let (calls, incidents) = deliver_with_threshold(Some(5)).await;
+
+        assert_eq!(calls, vec!["incident guardduty", "incident inspector", "incident macie"]);
+        assert!(incidents.get(&findings_resource(0)).await.unwrap().is_some());
+    }
+
+    /// Sink that posts to one channel and fails in another.
+    struct PartialNotifier;
+
+    #[async_trait]
+    impl Notifier for PartialNotifier {
+        fn name(&self) -> &'static str {
+            "slack"
+        }
+
+        async fn notify(&self, _summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+            Err(Box::new(PartialDelivery {
+                delivered: vec![MessageRef { sink: "slack".to_string(), channel: "C1".to_string(), id: "1.0001".to_string() }],
+                failed: vec!["#sec-alerts".to_string()],
+                error: "channel_not_found".to_string(),
+            }))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_delivery_keeps_messages_and_dead_letters_failed_channels() {
+        let findings = findings();
+
+        // Without a dead-letter store the finding is delivered again on the next emission
+        let mut new = pending(&findings[..1]);
+        let result = PartialNotifier.notify(&new[0].summary).await;
+        record_result(&PartialNotifier, &mut new[0], result, None).await;
+        assert!(new[0].incomplete);
+        assert_eq!(new[0].state.messages.len(), 1);
+
+        let dir = std::env::temp_dir().join(format!("dead-letters-partial-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let dead_letters = deadletter::FileDeadLetterStore::new(dir);
+
+        let mut new = pending(&findings[..1]);
+        let result = PartialNotifier.notify(&new[0].summary).await;
+        record_result(&PartialNotifier, &mut new[0], result, Some(&dead_letters)).await;
+        assert!(!new[0].incomplete);
+
+        let letters = dead_letters.list().await.unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].delivery.channels, vec!["#sec-alerts"]);
+    }
+}
@@ -0,0 +1,142 @@
+/// Default location of the icons shipped in the repository's `image_icons/` directory.
+pub const DEFAULT_ICON_BASE_URL: &str = "https://raw.githubusercontent.com/lep511/security-hub-to-slack/main/image_icons";
+
+const FALLBACK_ICON: &str = "Arch_AWS-Security-Hub_64.png";
+
+/// Icon file per product with the normalized aliases it is matched on. More specific
+/// aliases come first so e.g. "IAM Identity Center" does not match the IAM icon.
+const ICONS: &[(&str, &[&str])] = &[
+    ("Arch_AWS-IAM-Identity-Center_64.png", &["iamidentitycenter", "identitycenter", "sso"]),
+    ("Arch_AWS-Identity-and-Access-Management_64.png", &["accessanalyzer", "identityandaccessmanagement", "iam"]),
+    ("Arch_AWS-Firewall-Manager_64.png", &["firewallmanager", "fms"]),
+    ("Arch_AWS-Network-Firewall_64.png", &["networkfirewall"]),
+    ("Arch_AWS-Private-Certificate-Authority_64.png", &["privatecertificateauthority", "privateca", "acmpca"]),
+    ("Arch_AWS-Certificate-Manager_64.png", &["certificatemanager", "acm"]),
+    ("Arch_AWS-Artifact_64.png", &["artifact"]),
+    ("Arch_AWS-Audit-Manager_64.png", &["auditmanager"]),
+    ("Arch_AWS-CloudHSM_64.png", &["cloudhsm"]),
+    ("Arch_AWS-Config_64.png", &["config"]),
+    ("Arch_Amazon-Cloud-Directory_64.png", &["clouddirectory"]),
+    ("Arch_AWS-Directory-Service_64.png", &["directoryservice", "ds"]),
+    ("Arch_AWS-Key-Management-Service_64.png", &["keymanagementservice", "kms"]),
+    ("Arch_AWS-Resource-Access-Manager_64.png", &["resourceaccessmanager", "ram"]),
+    ("Arch_AWS-Secrets-Manager_64.png", &["secretsmanager"]),
+    ("Arch_Amazon-Security-Lake_64.png", &["securitylake"]),
+    ("Arch_AWS-Security-Hub_64.png", &["securityhub"]),
+    ("Arch_AWS-Shield_64.png", &["shield"]),
+    ("Arch_AWS-Signer_64.png", &["signer"]),
+    ("Arch_AWS-WAF_64.png", &["wafv2", "waf"]),
+    ("Arch_Amazon-Cognito_64.png", &["cognito"]),
+    ("Arch_Amazon-Detective_64.png", &["detective"]),
+    ("Arch_Amazon-GuardDuty_64.png", &["guardduty"]),
+    ("Arch_Amazon-Inspector_64.png", &["inspector"]),
+    ("Arch_Amazon-Macie_64.png", &["macie"]),
+    ("Arch_Amazon-Verified-Permissions_64.png", &["verifiedpermissions", "avp"]),
+];
+
+/// Maps products to the icons in `image_icons/`, served from a configurable base URL.
+#[derive(Debug, Clone)]
+pub struct IconRegistry {
+    base_url: String,
+}
+
+impl Default for IconRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_ICON_BASE_URL)
+    }
+}
+
+impl IconRegistry {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Reads `ICON_BASE_URL`, e.g. an S3 bucket or CloudFront distribution holding a copy
+    /// of `image_icons/`.
+    pub fn from_env() -> Self {
+        match std::env::var("ICON_BASE_URL") {
+            Ok(base_url) if !base_url.trim().is_empty() => Self::new(base_url.trim()),
+            _ => Self::default(),
+        }
+    }
+
+    /// Icon URL for a product, matched on the product identifier (last segment of
+    /// `metadata.product.uid`) first and the display name second.
+    pub fn icon_url(&self, product_uid: &str, product_name: &str) -> String {
+        let file = icon_file(product_uid)
+            .or_else(|| icon_file(product_name))
+            .unwrap_or(FALLBACK_ICON);
+        format!("{}/{}", self.base_url, file)
+    }
+}
+
+fn icon_file(product: &str) -> Option<&'static str> {
+    let normalized: String = product.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+    let stripped = normalized
+        .strip_prefix("amazon")
+        .or_else(|| normalized.strip_prefix("aws"))
+        .unwrap_or(&normalized);
+    if stripped.is_empty() {
+        return None;
+    }
+
+    // Short aliases such as "ram" or "iam" must match exactly to avoid false positives
+    ICONS.iter()
+        .find(|(_, aliases)| aliases.iter().any(|alias| {
+            *alias == stripped || (alias.len() >= 5 && stripped.contains(alias))
+        }))
+        .map(|(file, _)| *file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_bundled_icon_is_registered() {
+        let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../image_icons");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let file = entry.unwrap().file_name().into_string().unwrap();
+            assert!(ICONS.iter().any(|(icon, _)| *icon == file), "{} is not registered", file);
+        }
+    }
+
+    #[test]
+    fn test_fuzzy_product_matching() {
+        assert_eq!(icon_file("guardduty"), Some("Arch_Amazon-GuardDuty_64.png"));
+        assert_eq!(icon_file("inspector2"), Some("Arch_Amazon-Inspector_64.png"));
+        assert_eq!(icon_file("access-analyzer"), Some("Arch_AWS-Identity-and-Access-Management_64.png"));
+        assert_eq!(icon_file("IAM Access Analyzer"), Some("Arch_AWS-Identity-and-Access-Management_64.png"));
+        assert_eq!(icon_file("AWS IAM Identity Center"), Some("Arch_AWS-IAM-Identity-Center_64.png"));
+        assert_eq!(icon_file("Amazon Security Lake"), Some("Arch_Amazon-Security-Lake_64.png"));
+        assert_eq!(icon_file("firewall-manager"), Some("Arch_AWS-Firewall-Manager_64.png"));
+        assert_eq!(icon_file("AWS Network Firewall"), Some("Arch_AWS-Network-Firewall_64.png"));
+        assert_eq!(icon_file("kms"), Some("Arch_AWS-Key-Management-Service_64.png"));
+        assert_eq!(icon_file("Security Hub CSPM"), Some("Arch_AWS-Security-Hub_64.png"));
+        assert_eq!(icon_file("Program Analyzer"), None);
+        assert_eq!(icon_file(""), None);
+    }
+
+    #[test]
+    fn test_icon_url_uses_base_url_and_fallback() {
+        let icons = IconRegistry::new("https://cdn.example.com/icons/");
+
+        assert_eq!(
+            icons.icon_url("guardduty", "GuardDuty"),
+            "https://cdn.example.com/icons/Arch_Amazon-GuardDuty_64.png"
+        );
+        assert_eq!(
+            icons.icon_url("default", "Secrets Manager"),
+            "https://cdn.example.com/icons/Arch_AWS-Secrets-Manager_64.png"
+        );
+        assert_eq!(
+            icons.icon_url("default", "Acme Scanner"),
+            "https://cdn.example.com/icons/Arch_AWS-Security-Hub_64.png"
+        );
+    }
+}
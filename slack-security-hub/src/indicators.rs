@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use crate::struct_event::Finding;
+
+/// Network evidence and threat intelligence indicators of a finding.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Indicators {
+    pub domains: Vec<String>,
+    pub hostnames: Vec<String>,
+    pub protocols: Vec<String>,
+    pub directions: Vec<String>,
+    /// `Some(true)` only when every connection with a known outcome was blocked.
+    pub blocked: Option<bool>,
+    /// OSINT indicators as `(type, value)` pairs.
+    pub osint: Vec<(String, String)>,
+}
+
+impl Indicators {
+    pub fn from_finding(finding: &Finding) -> Self {
+        let mut indicators = Indicators::default();
+
+        for evidence in finding.evidences.as_deref().unwrap_or_default() {
+            if let Some(connection) = &evidence.connection_info {
+                push_unique(&mut indicators.directions, connection.direction.as_deref());
+                push_unique(&mut indicators.protocols, connection.protocol_name.as_deref());
+            }
+            if let Some(data) = &evidence.data {
+                push_unique(&mut indicators.domains, data.domain.as_deref());
+                push_unique(&mut indicators.protocols, data.protocol.as_deref());
+                if let Some(blocked) = data.blocked {
+                    indicators.blocked = Some(indicators.blocked.unwrap_or(true) && blocked);
+                }
+            }
+            if let Some(query) = &evidence.query {
+                push_unique(&mut indicators.hostnames, query.hostname.as_deref());
+            }
+        }
+
+        for osint in finding.osint.as_deref().unwrap_or_default() {
+            let Some(value) = osint.value.as_ref().or(osint.name.as_ref()) else {
+                continue;
+            };
+            let kind = osint.osint_type.clone().unwrap_or_else(|| "Indicator".to_string());
+            if !indicators.osint.iter().any(|(_, v)| v == value) {
+                indicators.osint.push((kind, value.clone()));
+            }
+        }
+
+        indicators
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.domains.is_empty()
+            && self.hostnames.is_empty()
+            && self.protocols.is_empty()
+            && self.blocked.is_none()
+            && self.osint.is_empty()
+    }
+}
+
+fn push_unique(values: &mut Vec<String>, value: Option<&str>) {
+    if let Some(value) = value.filter(|v| !v.is_empty())
+        && !values.iter().any(|v| v.eq_ignore_ascii_case(value))
+    {
+        values.push(value.to_string());
+    }
+}
+
+/// Makes an IOC non-clickable so Slack does not turn it into a live link,
+/// e.g. `http://evil.com` becomes `hxxp[://]evil[.]com`.
+pub fn defang(ioc: &str) -> String {
+    let mut defanged = ioc.to_string();
+    for (scheme, replacement) in [("https://", "hxxps[://]"), ("http://", "hxxp[://]")] {
+        if defanged.len() >= scheme.len() && defanged[..scheme.len()].eq_ignore_ascii_case(scheme) {
+            defanged = format!("{}{}", replacement, &defanged[scheme.len()..]);
+        }
+    }
+    defanged.replace('.', "[.]").replace('@', "[@]")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support;
+
+    #[test]
+    fn test_defang() {
+        assert_eq!(defang("c2.evil-domain.com"), "c2[.]evil-domain[.]com");
+        assert_eq!(defang("198.51.100.7"), "198[.]51[.]100[.]7");
+        assert_eq!(defang("https://evil.com/payload"), "hxxps[://]evil[.]com/payload");
+        assert_eq!(defang("HTTP://evil.com"), "hxxp[://]evil[.]com");
+        assert_eq!(defang("admin@evil.com"), "admin[@]evil[.]com");
+    }
+
+    #[test]
+    fn test_indicators_from_guardduty_dns_finding() {
+        let finding = test_support::finding(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let indicators = Indicators::from_finding(&finding);
+
+        assert_eq!(indicators.domains, vec!["c2.evil-domain.com"]);
+        assert_eq!(indicators.hostnames, vec!["c2.evil-domain.com"]);
+        assert_eq!(indicators.protocols, vec!["UDP"]);
+        assert_eq!(indicators.directions, vec!["Outbound"]);
+        assert_eq!(indicators.blocked, Some(false));
+        assert_eq!(indicators.osint, vec![("Domain".to_string(), "c2.evil-domain.com".to_string())]);
+    }
+
+    #[test]
+    fn test_blocked_only_when_all_connections_blocked() {
+        let finding: Finding = serde_json::from_value(serde_json::json!({
+            "evidences": [
+                {"data": {"blocked": true, "domain": "a.example"}},
+                {"data": {"blocked": false, "domain": "b.example"}}
+            ]
+        })).unwrap();
+        assert_eq!(Indicators::from_finding(&finding).blocked, Some(false));
+
+        let finding: Finding = serde_json::from_value(serde_json::json!({
+            "evidences": [{"data": {"blocked": true}}, {"query": {"hostname": "c.example"}}]
+        })).unwrap();
+        let indicators = Indicators::from_finding(&finding);
+        assert_eq!(indicators.blocked, Some(true));
+        assert_eq!(indicators.hostnames, vec!["c.example"]);
+
+        assert!(Indicators::from_finding(&Finding::default()).is_empty());
+    }
+}
@@ -0,0 +1,31 @@
+//! Security Hub findings to Slack, shared by the Lambda function and the `render-finding` CLI.
+
+pub mod struct_event;
+pub mod asff;
+pub mod slack_client;
+pub mod event_handler;
+pub mod routing;
+pub mod severity;
+pub mod dedup;
+pub mod state_store;
+pub mod pagerduty;
+pub mod notifier;
+pub mod teams;
+pub mod webhook;
+pub mod rate_limit;
+pub mod indicators;
+pub mod links;
+pub mod icons;
+pub mod blockkit;
+pub mod digest;
+pub mod suppression;
+pub mod s3;
+pub mod securityhub;
+pub mod posture;
+pub mod accounts;
+pub mod mentions;
+pub mod deadletter;
+pub mod products;
+pub mod compliance;
+pub mod risk;
+pub mod correlation;
@@ -0,0 +1,165 @@
+/// Partition of the standard, GovCloud and China regions. Console links are built
+/// for the partition of the finding so GovCloud and China findings open their own consoles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Partition {
+    Aws,
+    AwsUsGov,
+    AwsCn,
+}
+
+impl Partition {
+    /// Uses the `cloud_partition` reported by the finding, falling back to the region
+    /// prefix when it is missing.
+    pub fn resolve(partition: Option<&str>, region: &str) -> Self {
+        match partition {
+            Some("aws-us-gov") => Partition::AwsUsGov,
+            Some("aws-cn") => Partition::AwsCn,
+            Some("aws") => Partition::Aws,
+            _ if region.starts_with("us-gov-") => Partition::AwsUsGov,
+            _ if region.starts_with("cn-") => Partition::AwsCn,
+            _ => Partition::Aws,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Partition::Aws => "aws",
+            Partition::AwsUsGov => "aws-us-gov",
+            Partition::AwsCn => "aws-cn",
+        }
+    }
+
+    /// Console host for the region, without a trailing slash.
+    pub fn console_base(&self, region: &str) -> String {
+        match self {
+            Partition::Aws => format!("https://{}.console.aws.amazon.com", region),
+            Partition::AwsUsGov => "https://console.amazonaws-us-gov.com".to_string(),
+            Partition::AwsCn => "https://console.amazonaws.cn".to_string(),
+        }
+    }
+}
+
+/// Opens the finding in the Security Hub console, filtered by its identifier.
+pub fn security_hub_finding_url(partition: Partition, region: &str, finding_id: &str) -> String {
+    // The console expects the search filter to be encoded twice
+    let filter = encode(&format!("\\operator\\:EQUALS\\:{}", finding_id));
+    format!(
+        "{}/securityhub/home?region={}#/findings?search=Id%3D{}",
+        partition.console_base(region),
+        region,
+        encode(&filter)
+    )
+}
+
+/// Console of the AWS service that generated the finding. Returns `None` for third-party
+/// products, identified by a product ARN whose company segment is not `aws`.
+pub fn service_console_url(partition: Partition, region: &str, product_arn: &str) -> Option<String> {
+    let mut segments = product_arn.rsplit('/');
+    let service = segments.next().filter(|s| !s.is_empty())?;
+    if segments.next() != Some("aws") {
+        return None;
+    }
+
+    let path = match service {
+        "inspector" => "inspector/v2/home",
+        "macie" => "macie/home",
+        "access-analyzer" => "access-analyzer/home",
+        "firewall-manager" => "wafv2/fmsv2/home",
+        other => return Some(format!("{}/{}/home?region={}", partition.console_base(region), other, region)),
+    };
+    Some(format!("{}/{}?region={}", partition.console_base(region), path, region))
+}
+
+/// Console page of an affected resource. EC2 instances and S3 buckets open their own
+/// pages; any other ARN goes through the console's generic ARN resolver.
+pub fn resource_url(partition: Partition, region: &str, resource_id: &str) -> Option<String> {
+    let base = partition.console_base(region);
+
+    if let Some(instance_id) = resource_id.rsplit_once(":instance/").map(|(_, id)| id) {
+        return Some(format!("{}/ec2/home?region={}#InstanceDetails:instanceId={}", base, region, instance_id));
+    }
+    if let Some(bucket) = resource_id.split_once(":s3:::").map(|(_, bucket)| bucket)
+        && !bucket.contains('/')
+    {
+        return Some(format!("{}/s3/buckets/{}?region={}", base, bucket, region));
+    }
+    if resource_id.starts_with("arn:") {
+        return Some(format!("{}/go/view?arn={}", base, encode(resource_id)));
+    }
+    None
+}
+
+/// Percent-encodes everything but the RFC 3986 unreserved characters.
+pub fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partition_resolution() {
+        assert_eq!(Partition::resolve(Some("aws-us-gov"), "us-gov-west-1"), Partition::AwsUsGov);
+        assert_eq!(Partition::resolve(None, "us-gov-east-1"), Partition::AwsUsGov);
+        assert_eq!(Partition::resolve(None, "cn-north-1"), Partition::AwsCn);
+        assert_eq!(Partition::resolve(Some("aws"), "eu-west-1"), Partition::Aws);
+        assert_eq!(Partition::resolve(None, "unknown-region"), Partition::Aws);
+    }
+
+    #[test]
+    fn test_security_hub_finding_url() {
+        let url = security_hub_finding_url(
+            Partition::Aws,
+            "us-east-1",
+            "arn:aws:guardduty:us-east-1:111111111111:detector/12ab/finding/90c5",
+        );
+        assert_eq!(
+            url,
+            "https://us-east-1.console.aws.amazon.com/securityhub/home?region=us-east-1#/findings?search=Id%3D\
+             %255Coperator%255C%253AEQUALS%255C%253Aarn%253Aaws%253Aguardduty%253Aus-east-1%253A111111111111\
+             %253Adetector%252F12ab%252Ffinding%252F90c5"
+        );
+
+        let gov = security_hub_finding_url(Partition::AwsUsGov, "us-gov-west-1", "id");
+        assert!(gov.starts_with("https://console.amazonaws-us-gov.com/securityhub/home?region=us-gov-west-1#"));
+    }
+
+    #[test]
+    fn test_service_console_url() {
+        assert_eq!(
+            service_console_url(Partition::Aws, "us-east-1", "arn:aws:securityhub:us-east-1::productv2/aws/guardduty").unwrap(),
+            "https://us-east-1.console.aws.amazon.com/guardduty/home?region=us-east-1"
+        );
+        assert_eq!(
+            service_console_url(Partition::AwsCn, "cn-north-1", "arn:aws-cn:securityhub:cn-north-1::product/aws/inspector").unwrap(),
+            "https://console.amazonaws.cn/inspector/v2/home?region=cn-north-1"
+        );
+        assert!(service_console_url(Partition::Aws, "ap-southeast-2", "arn:aws:securityhub:ap-southeast-2:444444444444:product/444444444444/default").is_none());
+        assert!(service_console_url(Partition::Aws, "us-east-1", "").is_none());
+    }
+
+    #[test]
+    fn test_resource_url() {
+        assert_eq!(
+            resource_url(Partition::Aws, "us-east-1", "arn:aws:ec2:us-east-1:111111111111:instance/i-0abc123def4567890").unwrap(),
+            "https://us-east-1.console.aws.amazon.com/ec2/home?region=us-east-1#InstanceDetails:instanceId=i-0abc123def4567890"
+        );
+        assert_eq!(
+            resource_url(Partition::AwsUsGov, "us-gov-west-1", "arn:aws-us-gov:s3:::audit-logs").unwrap(),
+            "https://console.amazonaws-us-gov.com/s3/buckets/audit-logs?region=us-gov-west-1"
+        );
+        assert_eq!(
+            resource_url(Partition::Aws, "eu-west-1", "arn:aws:iam::222222222222:role/deploy").unwrap(),
+            "https://eu-west-1.console.aws.amazon.com/go/view?arn=arn%3Aaws%3Aiam%3A%3A222222222222%3Arole%2Fdeploy"
+        );
+        assert!(resource_url(Partition::Aws, "us-east-1", "bastion-01").is_none());
+    }
+}
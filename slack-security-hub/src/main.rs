@@ -1,9 +1,6 @@
 use lambda_runtime::{run, service_fn, tracing, Error};
 
-mod struct_event;
-mod slack_client;
-mod event_handler;
-use event_handler::function_handler;
+use slack_security_hub::event_handler::function_handler;
 
 
 #[tokio::main]
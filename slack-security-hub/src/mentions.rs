@@ -0,0 +1,127 @@
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::struct_event::FindingSummary;
+
+/// Maps resource tag values and account IDs to the Slack users or user groups that own
+/// them, so finding messages can mention the owning team.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OwnerMentions {
+    /// Resource tag names holding the owner, compared case-insensitively.
+    #[serde(default = "default_tag_keys")]
+    pub tag_keys: Vec<String>,
+    /// Tag value to Slack user (`U…`/`W…`) or user group (`S…`) ID.
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// Account ID to Slack ID, used when no resource tag maps to an owner.
+    #[serde(default)]
+    pub accounts: HashMap<String, String>,
+}
+
+fn default_tag_keys() -> Vec<String> {
+    vec!["owner".to_string(), "team".to_string()]
+}
+
+impl Default for OwnerMentions {
+    fn default() -> Self {
+        Self {
+            tag_keys: default_tag_keys(),
+            tags: HashMap::new(),
+            accounts: HashMap::new(),
+        }
+    }
+}
+
+impl OwnerMentions {
+    /// Loads the mapping from `OWNER_MENTIONS` (inline JSON) or `OWNER_MENTIONS_FILE`.
+    /// Without either variable nobody is mentioned.
+    pub fn from_env() -> Result<Self, Error> {
+        let json = if let Ok(json) = std::env::var("OWNER_MENTIONS") {
+            json
+        } else if let Ok(path) = std::env::var("OWNER_MENTIONS_FILE") {
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read owner mentions file '{}': {}", path, e))?
+        } else {
+            return Ok(Self::default());
+        };
+
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(json).map_err(|e| format!("Failed to parse owner mentions: {}", e))?)
+    }
+
+    /// Slack IDs of the owners of the finding's resources, falling back to the owner of
+    /// the account. Each owner appears once, in the order of the resource tags.
+    pub fn owners(&self, summary: &FindingSummary) -> Vec<String> {
+        let mut owners: Vec<String> = Vec::new();
+
+        for (name, value) in &summary.resource_tags {
+            if !self.tag_keys.iter().any(|key| key.eq_ignore_ascii_case(name)) {
+                continue;
+            }
+            if let Some(id) = self.tags.get(value)
+                && !owners.contains(id)
+            {
+                owners.push(id.clone());
+            }
+        }
+
+        if owners.is_empty()
+            && let Some(id) = self.accounts.get(&summary.account)
+        {
+            owners.push(id.clone());
+        }
+
+        owners
+    }
+}
+
+/// Slack mention syntax for a user or user group ID. Values already in `<…>` form, such
+/// as `<!here>`, are used as they are.
+pub fn mention(id: &str) -> String {
+    if id.starts_with('<') {
+        id.to_string()
+    } else if id.starts_with('S') {
+        format!("<!subteam^{}>", id)
+    } else {
+        format!("<@{}>", id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+
+    #[test]
+    fn test_mention_syntax() {
+        assert_eq!(mention("S0123ABC"), "<!subteam^S0123ABC>");
+        assert_eq!(mention("U0123ABC"), "<@U0123ABC>");
+        assert_eq!(mention("<!here>"), "<!here>");
+    }
+
+    #[test]
+    fn test_owners_from_tags_with_account_fallback() {
+        let mut summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        summary.resource_tags = vec![
+            ("Team".to_string(), "payments".to_string()),
+            ("env".to_string(), "payments".to_string()),
+            ("owner".to_string(), "payments".to_string()),
+            ("owner".to_string(), "alice".to_string()),
+        ];
+        let mentions = OwnerMentions::from_json(&format!(r#"{{
+            "tags": {{"payments": "S111", "alice": "U222"}},
+            "accounts": {{"{}": "S999"}}
+        }}"#, summary.account)).unwrap();
+
+        assert_eq!(mentions.owners(&summary), vec!["S111", "U222"]);
+
+        summary.resource_tags = vec![("owner".to_string(), "unknown".to_string())];
+        assert_eq!(mentions.owners(&summary), vec!["S999"]);
+
+        summary.account = "000000000000".to_string();
+        assert!(mentions.owners(&summary).is_empty());
+    }
+}
@@ -0,0 +1,505 @@
+use async_trait::async_trait;
+use lambda_runtime::{tracing, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+use crate::correlation::{incident_blocks, Incident, IncidentMessage};
+use crate::digest::{digest_blocks, DigestPolicy};
+use crate::routing::RoutingRules;
+use crate::securityhub::changed_in_slack;
+use crate::slack_client::{finding_blocks, SlackClient, SlackMessageRef};
+use crate::struct_event::FindingSummary;
+
+/// A message a sink posted for a finding, kept so later status changes can update it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MessageRef {
+    /// Name of the sink that posted the message. States recorded before other sinks kept
+    /// messages only hold Slack messages.
+    #[serde(default = "default_sink")]
+    pub sink: String,
+    pub channel: String,
+    /// Sink-specific message id, the message `ts` for Slack.
+    #[serde(alias = "ts")]
+    pub id: String,
+}
+
+fn default_sink() -> String {
+    "slack".to_string()
+}
+
+impl From<SlackMessageRef> for MessageRef {
+    fn from(message: SlackMessageRef) -> Self {
+        Self { sink: default_sink(), channel: message.channel, id: message.ts }
+    }
+}
+
+/// Error of a delivery that failed in some channels of a sink. The messages posted to the
+/// other channels are kept, and only the failed channels are delivered again.
+#[derive(Debug)]
+pub struct PartialDelivery {
+    pub delivered: Vec<MessageRef>,
+    /// Channels the finding was not delivered to.
+    pub failed: Vec<String>,
+    pub error: String,
+}
+
+impl std::fmt::Display for PartialDelivery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (failed channels: {})", self.error, self.failed.join(", "))
+    }
+}
+
+impl std::error::Error for PartialDelivery {}
+
+/// Per-channel outcome of a delivery to a multi-channel sink.
+#[derive(Default)]
+struct ChannelResults {
+    delivered: Vec<MessageRef>,
+    failed: Vec<String>,
+    last_error: Option<String>,
+}
+
+impl ChannelResults {
+    fn fail(&mut self, channel: &str, err_msg: String) {
+        tracing::error!("{}", err_msg);
+        self.failed.push(channel.to_string());
+        self.last_error = Some(err_msg);
+    }
+
+    /// The posted messages, or a `PartialDelivery` naming the failed channels.
+    fn into_result(self) -> Result<Vec<MessageRef>, Error> {
+        match self.last_error {
+            None => Ok(self.delivered),
+            Some(error) => Err(Box::new(PartialDelivery { delivered: self.delivered, failed: self.failed, error })),
+        }
+    }
+}
+
+/// Destination a finding is delivered to.
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Delivers a new finding. Returns the messages that were posted so they can be updated
+    /// later; sinks without updatable messages return an empty list.
+    async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error>;
+
+    /// Delivers a new finding to `channels` only, to replay the channels a partial delivery
+    /// missed. Sinks without channels deliver the finding again.
+    async fn notify_channels(&self, summary: &FindingSummary, _channels: &[String]) -> Result<Vec<MessageRef>, Error> {
+        self.notify(summary).await
+    }
+
+    /// Payload the sink sends for the finding, kept with failed deliveries for inspection.
+    fn render(&self, summary: &FindingSummary) -> Value {
+        serde_json::to_value(summary).unwrap_or_default()
+    }
+
+    /// Delivers a workflow status change of a previously delivered finding. `messages` holds
+    /// the messages of every sink; sinks that cannot update theirs deliver the finding again.
+    async fn notify_status_change(
+        &self,
+        summary: &FindingSummary,
+        _previous_status: &str,
+        _messages: &[MessageRef],
+    ) -> Result<Vec<MessageRef>, Error> {
+        self.notify(summary).await
+    }
+
+    /// Delivers a new finding correlated into `incident`, which already includes it. Sinks
+    /// without threads deliver the finding on its own.
+    async fn notify_incident(
+        &self,
+        summary: &FindingSummary,
+        _incident: &mut Incident,
+    ) -> Result<Vec<MessageRef>, Error> {
+        self.notify(summary).await
+    }
+
+    /// Delivers a large batch of new findings, returning the result of each finding in
+    /// order. Sinks without a digest format deliver the findings one by one.
+    async fn notify_digest(
+        &self,
+        summaries: &[&FindingSummary],
+        _policy: &DigestPolicy,
+    ) -> Vec<Result<Vec<MessageRef>, Error>> {
+        let mut results = Vec::with_capacity(summaries.len());
+        for summary in summaries {
+            results.push(self.notify(summary).await);
+        }
+        results
+    }
+}
+
+/// Sinks that can be enabled through the comma-separated `NOTIFIERS` variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifierKind {
+    Slack,
+    Teams,
+    Webhook,
+}
+
+impl NotifierKind {
+    /// Reads `NOTIFIERS`, defaulting to Slack only.
+    pub fn from_env() -> Result<Vec<Self>, Error> {
+        match std::env::var("NOTIFIERS") {
+            Ok(value) => Self::parse_list(&value)
+                .map_err(|e| format!("Invalid NOTIFIERS '{}': {}", value, e).into()),
+            Err(_) => Ok(vec![NotifierKind::Slack]),
+        }
+    }
+
+    pub fn parse_list(value: &str) -> Result<Vec<Self>, String> {
+        let mut kinds = Vec::new();
+        for kind in value.split(',').filter(|k| !k.trim().is_empty()) {
+            let kind: NotifierKind = kind.parse()?;
+            if !kinds.contains(&kind) {
+                kinds.push(kind);
+            }
+        }
+        Ok(kinds)
+    }
+}
+
+impl FromStr for NotifierKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "slack" => Ok(NotifierKind::Slack),
+            "teams" => Ok(NotifierKind::Teams),
+            "webhook" => Ok(NotifierKind::Webhook),
+            other => Err(format!("Unknown notifier: {}", other)),
+        }
+    }
+}
+
+/// Posts findings to the Slack channels selected by the routing rules.
+pub struct SlackNotifier {
+    client: SlackClient,
+    routing: RoutingRules,
+}
+
+impl SlackNotifier {
+    pub fn new(client: SlackClient, routing: RoutingRules) -> Self {
+        Self { client, routing }
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    fn render(&self, summary: &FindingSummary) -> Value {
+        serde_json::json!({ "blocks": finding_blocks(summary, self.client.icons()) })
+    }
+
+    async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+        self.notify_channels(summary, &self.routing.channels_for(summary)).await
+    }
+
+    async fn notify_channels(&self, summary: &FindingSummary, channels: &[String]) -> Result<Vec<MessageRef>, Error> {
+        // Reporting success would record the finding as delivered without posting it
+        if channels.is_empty() {
+            return Err(format!("No Slack channel routed for finding {}", summary.uid).into());
+        }
+
+        let mut results = ChannelResults::default();
+
+        // Post the finding summary to every Slack channel
+        for channel in channels {
+            match self.client.post_slack_message(channel, summary).await {
+                Ok(message) => results.delivered.push(message.into()),
+                Err(e) => results.fail(channel, format!("Failed to post finding to Slack channel {}: {}", channel, e)),
+            }
+        }
+
+        results.into_result()
+    }
+
+    async fn notify_status_change(
+        &self,
+        summary: &FindingSummary,
+        previous_status: &str,
+        messages: &[MessageRef],
+    ) -> Result<Vec<MessageRef>, Error> {
+        let messages: Vec<&MessageRef> = messages.iter().filter(|m| m.sink == self.name()).collect();
+        if messages.is_empty() {
+            return self.notify(summary).await;
+        }
+
+        // The buttons update the message themselves, re-rendering it would drop who acted
+        if changed_in_slack(summary) {
+            tracing::info!("Status of finding {} was changed from Slack, keeping its messages", summary.uid);
+            return Ok(messages.into_iter().cloned().collect());
+        }
+
+        let mut results = ChannelResults::default();
+
+        for message in messages {
+            let slack_message = SlackMessageRef { channel: message.channel.clone(), ts: message.id.clone() };
+            match self.client.update_finding_status(&slack_message, summary, previous_status).await {
+                Ok(_) => results.delivered.push(message.clone()),
+                Err(e) => results.fail(
+                    &message.channel,
+                    format!("Failed to update finding message in channel {}: {}", message.channel, e),
+                ),
+            }
+        }
+
+        results.into_result()
+    }
+
+    async fn notify_incident(
+        &self,
+        summary: &FindingSummary,
+        incident: &mut Incident,
+    ) -> Result<Vec<MessageRef>, Error> {
+        let blocks = serde_json::json!(incident_blocks(incident));
+
+        // Refresh the aggregated header of the incident in every channel it was posted to
+        for parent in &incident.parents {
+            if let Err(e) = self.client.update_slack_message_with_blocks(&parent.message, blocks.clone()).await {
+                tracing::error!("Failed to update incident message in channel {}: {}", parent.channel, e);
+            }
+        }
+
+        let mut results = ChannelResults::default();
+
+        for channel in self.routing.channels_for(summary) {
+            let parent = match incident.parent(&channel) {
+                Some(parent) => parent.clone(),
+                None => match self.client.post_slack_message_with_blocks(&channel, blocks.clone()).await {
+                    Ok(parent) => {
+                        incident.parents.push(IncidentMessage { channel: channel.clone(), message: parent.clone() });
+                        parent
+                    }
+                    Err(e) => {
+                        results.fail(&channel, format!("Failed to post incident to Slack channel {}: {}", channel, e));
+                        continue;
+                    }
+                },
+            };
+
+            match self.client.post_finding_reply(&parent, summary).await {
+                Ok(message) => results.delivered.push(message.into()),
+                Err(e) => results.fail(&channel, format!("Failed to post finding to incident thread in {}: {}", channel, e)),
+            }
+        }
+
+        results.into_result()
+    }
+
+    async fn notify_digest(
+        &self,
+        summaries: &[&FindingSummary],
+        policy: &DigestPolicy,
+    ) -> Vec<Result<Vec<MessageRef>, Error>> {
+        // Group the findings by routed channel, keeping the event order
+        let mut channels: Vec<(String, Vec<usize>)> = Vec::new();
+        for (index, summary) in summaries.iter().enumerate() {
+            for channel in self.routing.channels_for(summary) {
+                match channels.iter_mut().find(|(c, _)| *c == channel) {
+                    Some((_, indexes)) => indexes.push(index),
+                    None => channels.push((channel, vec![index])),
+                }
+            }
+        }
+
+        let mut results: Vec<ChannelResults> = summaries.iter().map(|_| ChannelResults::default()).collect();
+
+        for (channel, indexes) in channels {
+            let channel_summaries: Vec<&FindingSummary> = indexes.iter().map(|&i| summaries[i]).collect();
+            let blocks = serde_json::json!(digest_blocks(&channel_summaries, policy));
+
+            let digest = match self.client.post_slack_message_with_blocks(&channel, blocks).await {
+                Ok(digest) => digest,
+                Err(e) => {
+                    let err_msg = format!("Failed to post digest to Slack channel {}: {}", channel, e);
+                    tracing::error!("{}", err_msg);
+                    for &i in &indexes {
+                        results[i].failed.push(channel.clone());
+                        results[i].last_error = Some(err_msg.clone());
+                    }
+                    continue;
+                }
+            };
+
+            // Full details of each finding go in the digest thread
+            for &i in &indexes {
+                match self.client.post_finding_reply(&digest, summaries[i]).await {
+                    Ok(message) => results[i].delivered.push(message.into()),
+                    Err(e) => results[i].fail(&channel, format!("Failed to post finding to digest thread in {}: {}", channel, e)),
+                }
+            }
+        }
+
+        results.into_iter().map(ChannelResults::into_result).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+    use serde_json::{json, Value};
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_parse_notifier_list() {
+        assert_eq!(
+            NotifierKind::parse_list("slack, Teams,webhook,slack").unwrap(),
+            vec![NotifierKind::Slack, NotifierKind::Teams, NotifierKind::Webhook]
+        );
+        assert_eq!(NotifierKind::parse_list("teams").unwrap(), vec![NotifierKind::Teams]);
+        assert!(NotifierKind::parse_list("slack,email").is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slack_digest_threads_findings_per_channel() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .and(body_partial_json(json!({"channel": "#sec-alerts"})))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C1", "ts": "1.0001"})))
+            .mount(&server)
+            .await;
+
+        let routing = RoutingRules::from_json(r##"{
+            "default_channels": ["#sec-alerts"],
+            "routes": [{"name": "critical", "channels": ["#sec-incidents"], "match": {"min_severity": "Critical"}}]
+        }"##).unwrap();
+        let client = SlackClient::new("xoxb-test".to_string())
+            .with_api_url(server.uri())
+            .with_retry_policy(crate::slack_client::RetryPolicy { max_retries: 0, ..Default::default() });
+        let notifier = SlackNotifier::new(client, routing);
+
+        let critical = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let high = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        let results = notifier.notify_digest(&[&critical, &high, &critical], &DigestPolicy::default()).await;
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![MessageRef { sink: "slack".to_string(), channel: "C1".to_string(), id: "1.0001".to_string() }]);
+        assert!(results[1].is_err());
+        assert!(results[2].is_ok());
+
+        // One digest and two thread replies in #sec-incidents, one failed digest in #sec-alerts
+        let requests = server.received_requests().await.unwrap();
+        let bodies: Vec<Value> = requests.iter().map(|r| serde_json::from_slice(&r.body).unwrap()).collect();
+        assert_eq!(bodies.len(), 4);
+        assert_eq!(bodies[0]["blocks"][0]["text"]["text"], "2 new Security Hub findings");
+        assert_eq!(bodies.iter().filter(|b| b["thread_ts"] == "1.0001").count(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slack_incident_threads_related_findings() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C1", "ts": "1.0001"})))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.update"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C1", "ts": "1.0001"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = SlackClient::new("xoxb-test".to_string()).with_api_url(server.uri());
+        let notifier = SlackNotifier::new(client, RoutingRules::from_json(r##"{"default_channels": ["#sec-alerts"]}"##).unwrap());
+
+        let guardduty = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let mut inspector = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        inspector.resources[0].uid = guardduty.resources[0].uid.clone();
+
+        let mut incident = Incident::open(&inspector, 1_000);
+        incident.add(&inspector, 1_000);
+        notifier.notify_incident(&inspector, &mut incident).await.unwrap();
+        incident.add(&guardduty, 1_060);
+        notifier.notify_incident(&guardduty, &mut incident).await.unwrap();
+
+        assert_eq!(incident.parents.len(), 1);
+        assert_eq!(incident.parent("#sec-alerts").unwrap().ts, "1.0001");
+
+        // One parent message, one header update and both findings in its thread
+        let requests = server.received_requests().await.unwrap();
+        let bodies: Vec<Value> = requests.iter().map(|r| serde_json::from_slice(&r.body).unwrap()).collect();
+        assert_eq!(bodies.len(), 4);
+        assert_eq!(bodies[0]["blocks"][0]["text"]["text"], ":large_orange_circle: High incident: Inspector");
+        assert_eq!(bodies[2]["blocks"][0]["text"]["text"], ":red_circle: Critical incident: Inspector, GuardDuty");
+        assert_eq!(bodies.iter().filter(|b| b["thread_ts"] == "1.0001").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_unrouted_finding_is_not_delivered() {
+        let client = SlackClient::new("xoxb-test".to_string()).with_api_url("http://127.0.0.1:1");
+        let routing = RoutingRules::from_json(r##"{
+            "routes": [{"name": "prod", "channels": ["#sec-incidents"], "match": {"accounts": ["111111111111"]}}]
+        }"##).unwrap();
+        let notifier = SlackNotifier::new(client, routing);
+
+        let error = notifier.notify(&summary(include_str!("../fixtures/inspector_dev_high.json"))).await.unwrap_err();
+        assert!(error.to_string().contains("No Slack channel routed"));
+    }
+
+    #[tokio::test]
+    async fn test_status_change_from_slack_keeps_message() {
+        let server = MockServer::start().await;
+        let client = SlackClient::new("xoxb-test".to_string()).with_api_url(server.uri());
+        let notifier = SlackNotifier::new(client, RoutingRules::from_json(r##"{"default_channels": ["#sec-alerts"]}"##).unwrap());
+
+        let mut resolved = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        resolved.status = "Resolved".to_string();
+        resolved.comment = Some("Resolved by alice in Slack".to_string());
+        let messages = vec![
+            MessageRef { sink: "slack".to_string(), channel: "C1".to_string(), id: "1.0001".to_string() },
+            MessageRef { sink: "teams".to_string(), channel: String::new(), id: String::new() },
+        ];
+
+        let kept = notifier.notify_status_change(&resolved, "New", &messages).await.unwrap();
+
+        assert_eq!(kept, messages[..1]);
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slack_partial_failure_names_failed_channels() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .and(body_partial_json(json!({"channel": "#sec-alerts"})))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C2", "ts": "1.0002"})))
+            .mount(&server)
+            .await;
+
+        let client = SlackClient::new("xoxb-test".to_string())
+            .with_api_url(server.uri())
+            .with_retry_policy(crate::slack_client::RetryPolicy { max_retries: 0, ..Default::default() });
+        let routing = RoutingRules::from_json(r##"{"default_channels": ["#sec-alerts", "#sec-incidents"]}"##).unwrap();
+        let notifier = SlackNotifier::new(client, routing);
+        let summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+
+        let error = notifier.notify(&summary).await.unwrap_err();
+        let partial = error.downcast_ref::<PartialDelivery>().unwrap();
+        assert_eq!(partial.delivered.len(), 1);
+        assert_eq!(partial.failed, vec!["#sec-alerts"]);
+
+        // Replaying the failed channels leaves the others alone
+        let messages = notifier.notify_channels(&summary, &["#sec-incidents".to_string()]).await.unwrap();
+        assert_eq!(messages[0].channel, "C2");
+    }
+}
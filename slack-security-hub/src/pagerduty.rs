@@ -0,0 +1,293 @@
+use lambda_runtime::Error;
+use reqwest::Client;
+use serde_json::{json, Value};
+use crate::dedup::NotifyReason;
+use crate::severity::Severity;
+use crate::slack_client::is_closed_status;
+use crate::struct_event::FindingSummary;
+
+pub const DEFAULT_EVENTS_URL: &str = "https://events.pagerduty.com/v2/enqueue";
+
+/// Client for the PagerDuty Events API v2. Findings are keyed by `finding_info.uid`
+/// so a resolved or suppressed finding closes the incident it triggered.
+pub struct PagerDutyClient {
+    client: Client,
+    events_url: String,
+    routing_key: String,
+    /// Risk score that triggers an incident, replacing the High severity threshold.
+    min_risk_score: Option<u32>,
+}
+
+impl PagerDutyClient {
+    pub fn new(routing_key: String, events_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            events_url,
+            routing_key,
+            min_risk_score: None,
+        }
+    }
+
+    pub fn with_min_risk_score(mut self, min_risk_score: Option<u32>) -> Self {
+        self.min_risk_score = min_risk_score;
+        self
+    }
+
+    /// Escalates the finding, notified for `reason`: resolved and suppressed findings
+    /// resolve the incident, and High and above (or findings reaching the minimum risk
+    /// score, when configured) trigger one when they are new, reopened or more severe.
+    /// Other workflow status changes, such as an acknowledgement, leave the incident alone.
+    pub async fn escalate(&self, summary: &FindingSummary, reason: &NotifyReason) -> Result<(), Error> {
+        let escalated = match reason {
+            NotifyReason::New => true,
+            NotifyReason::SeverityChanged { from } => summary.severity > *from,
+            NotifyReason::StatusChanged { from } => is_closed_status(from),
+            NotifyReason::FingerprintChanged | NotifyReason::RenotifyIntervalElapsed => false,
+        };
+        let triggers = escalated && match self.min_risk_score {
+            Some(min) => summary.risk.as_ref().is_some_and(|r| r.score >= min),
+            None => summary.severity >= Severity::High,
+        };
+
+        if is_closed_status(&summary.status) {
+            self.send(&resolve_event(&self.routing_key, &summary.uid)).await
+        } else if triggers {
+            self.send(&trigger_event(&self.routing_key, summary)).await
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn send(&self, event: &Value) -> Result<(), Error> {
+        let response = self.client
+            .post(&self.events_url)
+            .json(event)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            Err(format!("PagerDuty error: {} {}", status, body).into())
+        }
+    }
+}
+
+pub fn trigger_event(routing_key: &str, summary: &FindingSummary) -> Value {
+    let mut event = json!({
+        "routing_key": routing_key,
+        "event_action": "trigger",
+        "dedup_key": summary.uid,
+        "payload": {
+            // PagerDuty truncates summaries longer than 1024 characters
+            "summary": summary.title.chars().take(1024).collect::<String>(),
+            "source": summary.resource_id,
+            "severity": pagerduty_severity(summary.severity),
+            "component": summary.product_name,
+            "group": summary.account,
+            "custom_details": {
+                "account": summary.account,
+                "region": summary.region,
+                "resource": summary.resource_id,
+                "status": summary.status,
+                "description": summary.description,
+                "first_seen": summary.first_seen,
+                "last_seen": summary.last_seen
+            }
+        }
+    });
+
+    if let Some(risk) = &summary.risk {
+        event["payload"]["custom_details"]["risk_score"] = json!(risk.score);
+        event["payload"]["custom_details"]["risk_factors"] = json!(risk.factors_text());
+    }
+
+    if summary.remediation != "no_remediation" {
+        event["links"] = json!([{ "href": summary.remediation, "text": "Remediation" }]);
+    }
+
+    event
+}
+
+pub fn resolve_event(routing_key: &str, dedup_key: &str) -> Value {
+    json!({
+        "routing_key": routing_key,
+        "event_action": "resolve",
+        "dedup_key": dedup_key
+    })
+}
+
+fn pagerduty_severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::Fatal => "critical",
+        Severity::High => "error",
+        Severity::Medium => "warning",
+        _ => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskScore;
+    use crate::struct_event::test_support::summary;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn client(server: &MockServer) -> PagerDutyClient {
+        PagerDutyClient::new("test-routing-key".to_string(), format!("{}/v2/enqueue", server.uri()))
+    }
+
+    #[test]
+    fn test_trigger_event_payload() {
+        let summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let event = trigger_event("key", &summary);
+
+        assert_eq!(event["event_action"], "trigger");
+        assert_eq!(event["dedup_key"], summary.uid);
+        assert_eq!(event["payload"]["severity"], "critical");
+        assert_eq!(event["payload"]["component"], "GuardDuty");
+        assert_eq!(event["payload"]["group"], "111111111111");
+        assert_eq!(event["links"][0]["text"], "Remediation");
+    }
+
+    #[tokio::test]
+    async fn test_high_severity_finding_triggers() {
+        let server = MockServer::start().await;
+        let summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        Mock::given(method("POST"))
+            .and(path("/v2/enqueue"))
+            .and(body_partial_json(json!({
+                "routing_key": "test-routing-key",
+                "event_action": "trigger",
+                "dedup_key": summary.uid,
+                "payload": { "severity": "error" }
+            })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({"status": "success"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        client(&server).escalate(&summary, &NotifyReason::New).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_resolved_finding_resolves() {
+        let server = MockServer::start().await;
+        let mut summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        summary.status = "Resolved".to_string();
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "event_action": "resolve", "dedup_key": summary.uid })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        client(&server).escalate(&summary, &NotifyReason::New).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_suppressed_finding_resolves() {
+        let server = MockServer::start().await;
+        let mut summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        summary.status = "Suppressed".to_string();
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "event_action": "resolve", "dedup_key": summary.uid })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let reason = NotifyReason::StatusChanged { from: "Notified".to_string() };
+        client(&server).escalate(&summary, &reason).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_acknowledged_finding_does_not_trigger_again() {
+        let server = MockServer::start().await;
+        let mut summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        summary.status = "Notified".to_string();
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        client.escalate(&summary, &NotifyReason::StatusChanged { from: "New".to_string() }).await.unwrap();
+        client.escalate(&summary, &NotifyReason::RenotifyIntervalElapsed).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_escalated_and_reopened_findings_trigger() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "event_action": "trigger" })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let client = client(&server);
+
+        let escalated = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        client.escalate(&escalated, &NotifyReason::SeverityChanged { from: Severity::Medium }).await.unwrap();
+
+        let mut reopened = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        reopened.status = "New".to_string();
+        client.escalate(&reopened, &NotifyReason::StatusChanged { from: "Resolved".to_string() }).await.unwrap();
+
+        // Less severe is not an escalation
+        let downgraded = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        client.escalate(&downgraded, &NotifyReason::SeverityChanged { from: Severity::Critical }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_low_severity_finding_is_not_sent() {
+        let server = MockServer::start().await;
+        let mut summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        summary.severity = Severity::Medium;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        client(&server).escalate(&summary, &NotifyReason::New).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_risk_score_threshold_replaces_severity() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({ "payload": { "custom_details": { "risk_score": 80 } } })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let client = client(&server).with_min_risk_score(Some(75));
+
+        let mut low_risk = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        low_risk.risk = Some(RiskScore { score: 40, ..Default::default() });
+        client.escalate(&low_risk, &NotifyReason::New).await.unwrap();
+
+        let mut high_risk = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        high_risk.severity = Severity::Medium;
+        high_risk.risk = Some(RiskScore { score: 80, ..Default::default() });
+        client.escalate(&high_risk, &NotifyReason::New).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_event_is_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(400).set_body_string("Invalid routing key"))
+            .mount(&server)
+            .await;
+
+        let summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let err = client(&server).escalate(&summary, &NotifyReason::New).await.unwrap_err();
+        assert!(err.to_string().contains("Invalid routing key"));
+    }
+}
@@ -0,0 +1,482 @@
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_securityhub::types::{AwsSecurityFinding, AwsSecurityFindingFilters, StringFilter, StringFilterComparison};
+use chrono::{DateTime, Utc};
+use lambda_runtime::{tracing, Error};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use crate::asff::AsffSeverity;
+use crate::blockkit;
+use crate::severity::Severity;
+use crate::slack_client::SlackClient;
+use crate::s3::{self, S3Uri};
+
+/// `detail-type` of events sent by EventBridge schedules, which trigger the posture report.
+pub const SCHEDULED_EVENT_DETAIL_TYPE: &str = "Scheduled Event";
+
+/// Number of oldest unresolved critical findings listed in the report.
+pub const TOP_OLDEST_CRITICALS: usize = 10;
+
+/// Rows listed per product and account breakdown.
+const MAX_BREAKDOWN_ROWS: usize = 10;
+
+/// An active, unresolved finding as seen by the posture report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostureFinding {
+    pub id: String,
+    pub title: String,
+    pub severity: Severity,
+    pub product: String,
+    pub account: String,
+    pub first_observed: DateTime<Utc>,
+}
+
+/// Source of the active findings the report is built from.
+#[async_trait]
+pub trait FindingsSource: Send + Sync {
+    async fn active_findings(&self) -> Result<Vec<PostureFinding>, Error>;
+}
+
+/// Reads active findings with a `NEW` or `NOTIFIED` workflow status via `GetFindings`.
+pub struct SecurityHubFindingsSource {
+    client: aws_sdk_securityhub::Client,
+}
+
+impl SecurityHubFindingsSource {
+    pub fn new(config: &SdkConfig) -> Self {
+        Self {
+            client: aws_sdk_securityhub::Client::new(config),
+        }
+    }
+}
+
+#[async_trait]
+impl FindingsSource for SecurityHubFindingsSource {
+    async fn active_findings(&self) -> Result<Vec<PostureFinding>, Error> {
+        let equals = |value: &str| StringFilter::builder()
+            .value(value)
+            .comparison(StringFilterComparison::Equals)
+            .build();
+        let filters = AwsSecurityFindingFilters::builder()
+            .record_state(equals("ACTIVE"))
+            .workflow_status(equals("NEW"))
+            .workflow_status(equals("NOTIFIED"))
+            .build();
+
+        let mut findings = Vec::new();
+        let mut pages = self.client
+            .get_findings()
+            .filters(filters)
+            .max_results(100)
+            .into_paginator()
+            .items()
+            .send();
+
+        while let Some(finding) = pages.next().await {
+            findings.push(posture_finding(&finding?));
+        }
+
+        Ok(findings)
+    }
+}
+
+fn posture_finding(finding: &AwsSecurityFinding) -> PostureFinding {
+    let severity = finding.severity()
+        .map(|s| AsffSeverity {
+            label: s.label().map(|l| l.as_str().to_string()),
+            normalized: s.normalized(),
+            ..Default::default()
+        }.to_severity())
+        .unwrap_or(Severity::Unknown);
+
+    let first_observed = finding.first_observed_at()
+        .or(finding.created_at())
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+
+    PostureFinding {
+        id: finding.id().unwrap_or_default().to_string(),
+        title: finding.title().unwrap_or("No title").to_string(),
+        severity,
+        product: finding.product_name().unwrap_or("Unknown Product").to_string(),
+        account: finding.aws_account_id().unwrap_or("unknown-account").to_string(),
+        first_observed,
+    }
+}
+
+/// Finding counts of one report, kept to show the delta in the next one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PostureSnapshot {
+    pub generated_at: DateTime<Utc>,
+    pub total: usize,
+    pub by_severity: BTreeMap<Severity, usize>,
+    pub by_product: BTreeMap<String, usize>,
+    pub by_account: BTreeMap<String, usize>,
+}
+
+impl PostureSnapshot {
+    pub fn from_findings(findings: &[PostureFinding], generated_at: DateTime<Utc>) -> Self {
+        let mut snapshot = Self {
+            generated_at,
+            total: findings.len(),
+            by_severity: BTreeMap::new(),
+            by_product: BTreeMap::new(),
+            by_account: BTreeMap::new(),
+        };
+        for finding in findings {
+            *snapshot.by_severity.entry(finding.severity).or_default() += 1;
+            *snapshot.by_product.entry(finding.product.clone()).or_default() += 1;
+            *snapshot.by_account.entry(finding.account.clone()).or_default() += 1;
+        }
+        snapshot
+    }
+}
+
+/// Persistence for the snapshot of the previous report.
+#[async_trait]
+pub trait SnapshotStore: Send + Sync {
+    async fn load(&self) -> Result<Option<PostureSnapshot>, Error>;
+    async fn save(&self, snapshot: &PostureSnapshot) -> Result<(), Error>;
+}
+
+/// Builds the configured snapshot store: `POSTURE_SNAPSHOT_S3_URI` (`s3://bucket/key`) or
+/// `POSTURE_SNAPSHOT_FILE`. Returns `None` when deltas are disabled.
+pub fn snapshot_store_from_env(config: &SdkConfig) -> Result<Option<Box<dyn SnapshotStore>>, Error> {
+    if let Ok(uri) = std::env::var("POSTURE_SNAPSHOT_S3_URI") {
+        let location = S3Uri::object(&uri)
+            .map_err(|e| format!("Invalid POSTURE_SNAPSHOT_S3_URI '{}': {}", uri, e))?;
+        Ok(Some(Box::new(S3SnapshotStore {
+            client: aws_sdk_s3::Client::new(config),
+            bucket: location.bucket,
+            key: location.key,
+        })))
+    } else if let Ok(path) = std::env::var("POSTURE_SNAPSHOT_FILE") {
+        Ok(Some(Box::new(FileSnapshotStore { path: path.into() })))
+    } else {
+        Ok(None)
+    }
+}
+
+pub struct S3SnapshotStore {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    key: String,
+}
+
+#[async_trait]
+impl SnapshotStore for S3SnapshotStore {
+    async fn load(&self) -> Result<Option<PostureSnapshot>, Error> {
+        match s3::get_object(&self.client, &self.bucket, &self.key).await? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn save(&self, snapshot: &PostureSnapshot) -> Result<(), Error> {
+        self.client
+            .put_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .content_type("application/json")
+            .body(serde_json::to_vec(snapshot)?.into())
+            .send()
+            .await?;
+        Ok(())
+    }
+}
+
+pub struct FileSnapshotStore {
+    path: PathBuf,
+}
+
+#[async_trait]
+impl SnapshotStore for FileSnapshotStore {
+    async fn load(&self) -> Result<Option<PostureSnapshot>, Error> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(format!("Failed to read snapshot file '{}': {}", self.path.display(), e).into()),
+        }
+    }
+
+    async fn save(&self, snapshot: &PostureSnapshot) -> Result<(), Error> {
+        std::fs::write(&self.path, serde_json::to_string_pretty(snapshot)?)
+            .map_err(|e| format!("Failed to write snapshot file '{}': {}", self.path.display(), e).into())
+    }
+}
+
+/// Builds the report from the active findings, posts it to `channel` and saves the new
+/// snapshot for the next report's delta.
+pub async fn run_report(
+    source: &dyn FindingsSource,
+    snapshots: Option<&dyn SnapshotStore>,
+    slack: &SlackClient,
+    channel: &str,
+    now: DateTime<Utc>,
+) -> Result<(), Error> {
+    let findings = source.active_findings().await?;
+    tracing::info!("Building posture report from {} active findings", findings.len());
+
+    let previous = match snapshots {
+        // A missing previous snapshot only drops the delta from the report
+        Some(store) => store.load().await.unwrap_or_else(|e| {
+            tracing::error!("Failed to load the previous posture snapshot: {}", e);
+            None
+        }),
+        None => None,
+    };
+
+    let current = PostureSnapshot::from_findings(&findings, now);
+    let blocks = report_blocks(&current, previous.as_ref(), &findings);
+    slack.post_slack_message_with_blocks(channel, json!(blocks)).await
+        .map_err(|e| format!("Failed to post posture report to Slack: {}", e))?;
+
+    if let Some(store) = snapshots {
+        store.save(&current).await?;
+    }
+    Ok(())
+}
+
+pub fn report_blocks(
+    current: &PostureSnapshot,
+    previous: Option<&PostureSnapshot>,
+    findings: &[PostureFinding],
+) -> Vec<Value> {
+    let mut summary = format!(
+        "*{}* active findings{}",
+        current.total,
+        previous.map(|p| delta(current.total, p.total)).unwrap_or_default()
+    );
+    if let Some(previous) = previous {
+        summary.push_str(&format!(" since {}", previous.generated_at.format("%Y-%m-%d")));
+    }
+
+    // Severities that dropped to zero since the previous report are still listed
+    let severities: BTreeSet<Severity> = current.by_severity.keys()
+        .chain(previous.into_iter().flat_map(|p| p.by_severity.keys()))
+        .copied()
+        .collect();
+    let severity_lines: Vec<String> = severities.iter()
+        .rev()
+        .map(|severity| {
+            let count = current.by_severity.get(severity).copied().unwrap_or(0);
+            let previous = previous.map(|p| p.by_severity.get(severity).copied().unwrap_or(0));
+            format!("*{}*: {}{}", severity, count, previous.map(|p| delta(count, p)).unwrap_or_default())
+        })
+        .collect();
+
+    let mut blocks = vec![
+        blockkit::header("Security Hub posture report"),
+        json!({
+            "type": "context",
+            "elements": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("{}  |  Generated {}", summary, current.generated_at.format("%Y-%m-%d %H:%M UTC"))
+                }
+            ]
+        }),
+        json!({
+            "type": "section",
+            "fields": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("*By severity*\n{}", or_none(&severity_lines))
+                },
+                {
+                    "type": "mrkdwn",
+                    "text": format!(
+                        "*By product*\n{}",
+                        or_none(&breakdown(&current.by_product, previous.map(|p| &p.by_product)))
+                    )
+                }
+            ]
+        }),
+    ];
+
+    blocks.extend(blockkit::mrkdwn_sections(
+        &format!(
+            "*By account*\n{}",
+            or_none(&breakdown(&current.by_account, previous.map(|p| &p.by_account)))
+        ),
+        None,
+    ));
+
+    let mut criticals: Vec<&PostureFinding> = findings.iter()
+        .filter(|f| f.severity >= Severity::Critical)
+        .collect();
+    criticals.sort_by_key(|f| f.first_observed);
+    let critical_lines: Vec<String> = criticals.iter()
+        .take(TOP_OLDEST_CRITICALS)
+        .map(|f| format!(
+            "• *{}d*  {}  _({} | {})_",
+            (current.generated_at - f.first_observed).num_days(),
+            f.title,
+            f.product,
+            f.account,
+        ))
+        .collect();
+
+    blocks.extend(blockkit::mrkdwn_sections(
+        &format!("*Oldest unresolved critical findings*\n{}", or_none(&critical_lines)),
+        None,
+    ));
+
+    blockkit::enforce_limits(blocks)
+}
+
+/// Rows of the largest counts, with the change since the previous report.
+fn breakdown(current: &BTreeMap<String, usize>, previous: Option<&BTreeMap<String, usize>>) -> Vec<String> {
+    let keys: BTreeSet<&String> = current.keys().chain(previous.into_iter().flat_map(|p| p.keys())).collect();
+    let mut rows: Vec<(&String, usize)> = keys.into_iter()
+        .map(|key| (key, current.get(key).copied().unwrap_or(0)))
+        .collect();
+    rows.sort_by_key(|(_, count)| Reverse(*count));
+
+    let mut lines: Vec<String> = rows.iter()
+        .take(MAX_BREAKDOWN_ROWS)
+        .map(|(key, count)| {
+            let previous = previous.map(|p| p.get(*key).copied().unwrap_or(0));
+            format!("{}: {}{}", key, count, previous.map(|p| delta(*count, p)).unwrap_or_default())
+        })
+        .collect();
+    if rows.len() > MAX_BREAKDOWN_ROWS {
+        lines.push(format!("_+{} more_", rows.len() - MAX_BREAKDOWN_ROWS));
+    }
+    lines
+}
+
+fn delta(current: usize, previous: usize) -> String {
+    match current as i64 - previous as i64 {
+        0 => " (no change)".to_string(),
+        change if change > 0 => format!(" (+{})", change),
+        change => format!(" ({})", change),
+    }
+}
+
+fn or_none(lines: &[String]) -> String {
+    if lines.is_empty() {
+        "_None_".to_string()
+    } else {
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    struct StubSource(Vec<PostureFinding>);
+
+    #[async_trait]
+    impl FindingsSource for StubSource {
+        async fn active_findings(&self) -> Result<Vec<PostureFinding>, Error> {
+            Ok(self.0.clone())
+        }
+    }
+
+    #[derive(Default)]
+    struct MemorySnapshotStore(Mutex<Option<PostureSnapshot>>);
+
+    #[async_trait]
+    impl SnapshotStore for MemorySnapshotStore {
+        async fn load(&self) -> Result<Option<PostureSnapshot>, Error> {
+            Ok(self.0.lock().unwrap().clone())
+        }
+
+        async fn save(&self, snapshot: &PostureSnapshot) -> Result<(), Error> {
+            *self.0.lock().unwrap() = Some(snapshot.clone());
+            Ok(())
+        }
+    }
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        timestamp.parse().unwrap()
+    }
+
+    fn finding(id: &str, severity: Severity, product: &str, account: &str, first_observed: &str) -> PostureFinding {
+        PostureFinding {
+            id: id.to_string(),
+            title: format!("Finding {}", id),
+            severity,
+            product: product.to_string(),
+            account: account.to_string(),
+            first_observed: at(first_observed),
+        }
+    }
+
+    fn findings() -> Vec<PostureFinding> {
+        vec![
+            finding("1", Severity::Critical, "GuardDuty", "111111111111", "2026-10-01T00:00:00Z"),
+            finding("2", Severity::Critical, "Inspector", "222222222222", "2026-08-01T00:00:00Z"),
+            finding("3", Severity::High, "Inspector", "222222222222", "2026-10-10T00:00:00Z"),
+            finding("4", Severity::Medium, "Inspector", "111111111111", "2026-10-11T00:00:00Z"),
+        ]
+    }
+
+    #[test]
+    fn test_report_without_previous_snapshot() {
+        let now = at("2026-10-12T08:00:00Z");
+        let current = PostureSnapshot::from_findings(&findings(), now);
+        let blocks = report_blocks(&current, None, &findings());
+
+        assert_eq!(blocks[1]["elements"][0]["text"], "*4* active findings  |  Generated 2026-10-12 08:00 UTC");
+        assert_eq!(blocks[2]["fields"][0]["text"], "*By severity*\n*Critical*: 2\n*High*: 1\n*Medium*: 1");
+        assert_eq!(blocks[2]["fields"][1]["text"], "*By product*\nInspector: 3\nGuardDuty: 1");
+        // Oldest critical first
+        assert_eq!(
+            blocks[4]["text"]["text"],
+            "*Oldest unresolved critical findings*\n\
+             • *72d*  Finding 2  _(Inspector | 222222222222)_\n\
+             • *11d*  Finding 1  _(GuardDuty | 111111111111)_"
+        );
+    }
+
+    #[test]
+    fn test_report_shows_delta_against_previous_snapshot() {
+        let previous = PostureSnapshot::from_findings(&findings()[1..], at("2026-10-05T08:00:00Z"));
+        let current = PostureSnapshot::from_findings(&findings()[..3], at("2026-10-12T08:00:00Z"));
+        let blocks = report_blocks(&current, Some(&previous), &findings()[..3]);
+
+        assert!(blocks[1]["elements"][0]["text"].as_str().unwrap()
+            .starts_with("*3* active findings (no change) since 2026-10-05"));
+        assert_eq!(
+            blocks[2]["fields"][0]["text"],
+            "*By severity*\n*Critical*: 2 (+1)\n*High*: 1 (no change)\n*Medium*: 0 (-1)"
+        );
+        assert_eq!(blocks[3]["text"]["text"], "*By account*\n222222222222: 2 (no change)\n111111111111: 1 (no change)");
+    }
+
+    #[tokio::test]
+    async fn test_run_report_posts_and_saves_snapshot() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C1", "ts": "1.0001"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let store = MemorySnapshotStore::default();
+        let slack = SlackClient::new("xoxb-test".to_string()).with_api_url(server.uri());
+        let now = at("2026-10-12T08:00:00Z");
+
+        run_report(&StubSource(findings()), Some(&store), &slack, "#sec-posture", now).await.unwrap();
+
+        let saved = store.load().await.unwrap().unwrap();
+        assert_eq!(saved.total, 4);
+        assert_eq!(saved.by_severity[&Severity::Critical], 2);
+        assert_eq!(saved.generated_at, now);
+
+        let body: Value = serde_json::from_slice(&server.received_requests().await.unwrap()[0].body).unwrap();
+        assert_eq!(body["channel"], "#sec-posture");
+        assert_eq!(body["blocks"][0]["text"]["text"], "Security Hub posture report");
+    }
+}
@@ -0,0 +1,407 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use crate::blockkit;
+use crate::indicators::defang;
+use crate::struct_event::{Finding, Resource};
+
+/// Packages listed per vulnerability before the rest are summarized.
+const MAX_RENDERED_PACKAGES: usize = 5;
+
+/// Details only some products report, selected by `metadata.product.name` and rendered
+/// below the generic finding details.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "product", rename_all = "snake_case")]
+pub enum ProductDetails {
+    #[default]
+    Generic,
+    Inspector(InspectorDetails),
+    GuardDuty(GuardDutyDetails),
+    Macie(MacieDetails),
+    AccessAnalyzer(AccessAnalyzerDetails),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InspectorDetails {
+    pub vulnerabilities: Vec<VulnerabilityDetails>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VulnerabilityDetails {
+    pub cve: String,
+    pub cvss_score: Option<f64>,
+    pub cvss_version: Option<String>,
+    pub exploit_available: Option<bool>,
+    pub packages: Vec<PackageDetails>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PackageDetails {
+    pub name: String,
+    pub version: Option<String>,
+    pub fixed_version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GuardDutyDetails {
+    pub actor_ip: Option<String>,
+    /// `City, Country` of the actor IP.
+    pub location: Option<String>,
+    /// Autonomous system of the actor IP, e.g. `AS64496 Example Hosting`.
+    pub network: Option<String>,
+    /// Service and operation, e.g. `s3.amazonaws.com GetBucketAcl`.
+    pub api_call: Option<String>,
+    pub caller: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MacieDetails {
+    pub bucket: Option<String>,
+    pub object: Option<String>,
+    /// Sensitive data types with their number of occurrences, most frequent first.
+    pub sensitive_data: Vec<(String, i64)>,
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccessAnalyzerDetails {
+    pub principals: Vec<String>,
+    pub actions: Vec<String>,
+    pub is_public: Option<bool>,
+}
+
+impl ProductDetails {
+    pub fn from_finding(finding: &Finding, product_name: &str) -> Self {
+        let product: String = product_name.chars()
+            .filter(char::is_ascii_alphanumeric)
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if product.contains("inspector") {
+            InspectorDetails::from_finding(finding).map_or(Self::Generic, Self::Inspector)
+        } else if product.contains("guardduty") {
+            GuardDutyDetails::from_finding(finding).map_or(Self::Generic, Self::GuardDuty)
+        } else if product.contains("macie") {
+            MacieDetails::from_finding(finding).map_or(Self::Generic, Self::Macie)
+        } else if product.contains("accessanalyzer") {
+            AccessAnalyzerDetails::from_finding(finding).map_or(Self::Generic, Self::AccessAnalyzer)
+        } else {
+            Self::Generic
+        }
+    }
+
+    /// Slack blocks of the product-specific renderer, empty for generic findings.
+    pub fn blocks(&self) -> Vec<Value> {
+        let text = match self {
+            Self::Generic => return Vec::new(),
+            Self::Inspector(details) => details.text(),
+            Self::GuardDuty(details) => details.text(),
+            Self::Macie(details) => details.text(),
+            Self::AccessAnalyzer(details) => details.text(),
+        };
+        blockkit::mrkdwn_sections(&text, None)
+    }
+}
+
+impl InspectorDetails {
+    fn from_finding(finding: &Finding) -> Option<Self> {
+        let vulnerabilities: Vec<VulnerabilityDetails> = finding.vulnerabilities.iter()
+            .flatten()
+            .filter_map(|vulnerability| {
+                let cve = vulnerability.cve.as_ref()?;
+                // Prefer the most recent CVSS version
+                let cvss = cve.cvss.iter()
+                    .flatten()
+                    .filter(|c| c.base_score.is_some())
+                    .max_by(|a, b| a.version.cmp(&b.version));
+
+                Some(VulnerabilityDetails {
+                    cve: cve.uid.clone()?,
+                    cvss_score: cvss.and_then(|c| c.base_score),
+                    cvss_version: cvss.and_then(|c| c.version.clone()),
+                    exploit_available: vulnerability.is_exploit_available,
+                    packages: vulnerability.affected_packages.iter()
+                        .flatten()
+                        .filter_map(|package| Some(PackageDetails {
+                            name: package.name.clone()?,
+                            version: package.version.clone(),
+                            fixed_version: package.fixed_in_version.clone(),
+                        }))
+                        .collect(),
+                })
+            })
+            .collect();
+
+        (!vulnerabilities.is_empty()).then_some(Self { vulnerabilities })
+    }
+
+    fn text(&self) -> String {
+        let mut lines = vec![":shield: *Vulnerability*".to_string()];
+
+        for vulnerability in &self.vulnerabilities {
+            let mut line = format!("*{}*", vulnerability.cve);
+            if let Some(score) = vulnerability.cvss_score {
+                line.push_str(&format!("  CVSS {:.1}", score));
+                if let Some(version) = &vulnerability.cvss_version {
+                    line.push_str(&format!(" (v{})", version));
+                }
+            }
+            if vulnerability.exploit_available == Some(true) {
+                line.push_str("  :warning: Exploit available");
+            }
+            lines.push(line);
+
+            for package in vulnerability.packages.iter().take(MAX_RENDERED_PACKAGES) {
+                let installed = match &package.version {
+                    Some(version) => format!("`{} {}`", package.name, version),
+                    None => format!("`{}`", package.name),
+                };
+                let fix = match &package.fixed_version {
+                    Some(fixed) => format!("fixed in `{}`", fixed),
+                    None => "no fix available".to_string(),
+                };
+                lines.push(format!("Package {} → {}", installed, fix));
+            }
+            if vulnerability.packages.len() > MAX_RENDERED_PACKAGES {
+                lines.push(format!("+{} more packages", vulnerability.packages.len() - MAX_RENDERED_PACKAGES));
+            }
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl GuardDutyDetails {
+    fn from_finding(finding: &Finding) -> Option<Self> {
+        let evidences = finding.evidences.as_deref().unwrap_or_default();
+        let endpoint = evidences.iter().find_map(|e| e.src_endpoint.as_ref());
+        let api = evidences.iter().find_map(|e| e.api.as_ref());
+        let user = evidences.iter().find_map(|e| e.actor.as_ref().and_then(|a| a.user.as_ref()));
+
+        let location = endpoint
+            .and_then(|e| e.location.as_ref())
+            .map(|l| [&l.city, &l.country].into_iter().flatten().cloned().collect::<Vec<_>>().join(", "))
+            .filter(|l| !l.is_empty());
+        let network = endpoint
+            .and_then(|e| e.autonomous_system.as_ref())
+            .map(|asn| match (asn.number, &asn.name) {
+                (Some(number), Some(name)) => format!("AS{} {}", number, name),
+                (Some(number), None) => format!("AS{}", number),
+                (None, Some(name)) => name.clone(),
+                (None, None) => String::new(),
+            })
+            .filter(|n| !n.is_empty());
+        let api_call = api.and_then(|api| {
+            let operation = api.operation.as_deref()?;
+            Some(match api.service.as_ref().and_then(|s| s.name.as_deref()) {
+                Some(service) => format!("{} {}", service, operation),
+                None => operation.to_string(),
+            })
+        });
+
+        let details = Self {
+            actor_ip: endpoint.and_then(|e| e.ip.clone()),
+            location,
+            network,
+            api_call,
+            caller: user.and_then(|u| u.name.clone().or_else(|| u.uid.clone())),
+        };
+
+        (details != Self::default()).then_some(details)
+    }
+
+    fn text(&self) -> String {
+        let mut lines = vec![":detective: *Actor*".to_string()];
+
+        if let Some(ip) = &self.actor_ip {
+            let mut line = format!("IP: `{}`", defang(ip));
+            let origin: Vec<&str> = [&self.location, &self.network].into_iter().flatten().map(String::as_str).collect();
+            if !origin.is_empty() {
+                line.push_str(&format!(" ({})", origin.join(" | ")));
+            }
+            lines.push(line);
+        }
+        if let Some(api_call) = &self.api_call {
+            let mut line = format!("API call: `{}`", api_call);
+            if let Some(caller) = &self.caller {
+                line.push_str(&format!(" by `{}`", caller));
+            }
+            lines.push(line);
+        } else if let Some(caller) = &self.caller {
+            lines.push(format!("Caller: `{}`", caller));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl MacieDetails {
+    fn from_finding(finding: &Finding) -> Option<Self> {
+        let resources = finding.resources.as_deref().unwrap_or_default();
+        let uid_of = |resource_type: &str| resources.iter()
+            .find(|r| r.resource_type.as_deref() == Some(resource_type))
+            .and_then(|r| r.uid.as_deref())
+            .map(|uid| uid.trim_start_matches("arn:aws:s3:::").to_string());
+
+        let mut sensitive_data: Vec<(String, i64)> = Vec::new();
+        let mut total = None;
+        for classification in resources.iter().flat_map(|r| r.data_classifications.iter().flatten()) {
+            if let Some(count) = classification.total {
+                total = Some(total.unwrap_or(0) + count);
+            }
+            for detail in classification.discovery_details.iter().flatten() {
+                let Some(data_type) = detail.detail_type.clone() else {
+                    continue;
+                };
+                let count = detail.count.unwrap_or(0);
+                match sensitive_data.iter_mut().find(|(t, _)| *t == data_type) {
+                    Some((_, existing)) => *existing += count,
+                    None => sensitive_data.push((data_type, count)),
+                }
+            }
+        }
+        sensitive_data.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+        let details = Self {
+            bucket: uid_of("AWS::S3::Bucket"),
+            object: uid_of("AWS::S3::Object"),
+            sensitive_data,
+            total,
+        };
+
+        (details != Self::default()).then_some(details)
+    }
+
+    fn text(&self) -> String {
+        let mut lines = vec![":lock: *Sensitive data*".to_string()];
+
+        match (&self.object, &self.bucket) {
+            (Some(object), _) => lines.push(format!("Object: `s3://{}`", object)),
+            (None, Some(bucket)) => lines.push(format!("Bucket: `{}`", bucket)),
+            (None, None) => {}
+        }
+        if !self.sensitive_data.is_empty() {
+            let types: Vec<String> = self.sensitive_data.iter()
+                .map(|(data_type, count)| format!("{} ({})", data_type, count))
+                .collect();
+            lines.push(format!("Detected: {}", types.join(", ")));
+        }
+        if let Some(total) = self.total {
+            lines.push(format!("Total occurrences: {}", total));
+        }
+
+        lines.join("\n")
+    }
+}
+
+impl AccessAnalyzerDetails {
+    fn from_finding(finding: &Finding) -> Option<Self> {
+        let data = finding.resources.iter()
+            .flatten()
+            .find_map(|r: &Resource| r.data.as_ref())?;
+
+        let details = Self {
+            principals: data.get("principal").map(strings).unwrap_or_default(),
+            actions: data.get("action").map(strings).unwrap_or_default(),
+            is_public: data.get("isPublic").and_then(Value::as_bool),
+        };
+
+        (details != Self::default()).then_some(details)
+    }
+
+    fn text(&self) -> String {
+        let mut lines = vec![":unlock: *External access*".to_string()];
+
+        if self.is_public == Some(true) {
+            lines.push("*Public access*".to_string());
+        }
+        if !self.principals.is_empty() {
+            lines.push(format!("Principal: {}", code_list(&self.principals)));
+        }
+        if !self.actions.is_empty() {
+            lines.push(format!("Access: {}", code_list(&self.actions)));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Strings of a policy element, which may be a string, a list, or a map such as
+/// `{"AWS": ["arn:..."]}`.
+fn strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(values) => values.iter().flat_map(strings).collect(),
+        Value::Object(map) => map.values().flat_map(strings).collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn code_list(values: &[String]) -> String {
+    values.iter().map(|v| format!("`{}`", v)).collect::<Vec<_>>().join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::icons::IconRegistry;
+    use crate::slack_client::finding_blocks;
+    use crate::struct_event::test_support::summary;
+
+    /// Compares the rendered message with `fixtures/golden/<name>.json`. Run the tests with
+    /// `UPDATE_GOLDEN=1` to rewrite the golden files after an intended format change.
+    fn assert_golden(name: &str, fixture: &str) {
+        let blocks = Value::Array(finding_blocks(&summary(fixture), &IconRegistry::default()));
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join(format!("fixtures/golden/{}.json", name));
+
+        if std::env::var("UPDATE_GOLDEN").is_ok() {
+            std::fs::write(&path, serde_json::to_string_pretty(&blocks).unwrap() + "\n").unwrap();
+        }
+
+        let golden: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(blocks, golden, "rendered message differs from {}", path.display());
+    }
+
+    #[test]
+    fn test_inspector_golden() {
+        assert_golden("inspector", include_str!("../fixtures/inspector_dev_high.json"));
+    }
+
+    #[test]
+    fn test_guardduty_golden() {
+        assert_golden("guardduty", include_str!("../fixtures/guardduty_api_call.json"));
+    }
+
+    #[test]
+    fn test_macie_golden() {
+        assert_golden("macie", include_str!("../fixtures/macie_sensitive_data.json"));
+    }
+
+    #[test]
+    fn test_access_analyzer_golden() {
+        assert_golden("access_analyzer", include_str!("../fixtures/access_analyzer_external_access.json"));
+    }
+
+    #[test]
+    fn test_details_selected_by_product_name() {
+        let inspector = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        let ProductDetails::Inspector(details) = &inspector.product_details else {
+            panic!("expected Inspector details, got {:?}", inspector.product_details);
+        };
+        assert_eq!(details.vulnerabilities[0].cve, "CVE-2024-45491");
+        assert_eq!(details.vulnerabilities[0].cvss_score, Some(9.8));
+        assert_eq!(details.vulnerabilities[0].packages[0].fixed_version.as_deref(), Some("2.5.0-1+deb12u1"));
+
+        // GuardDuty findings without actor evidence keep the generic rendering
+        let dns = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        assert_eq!(dns.product_details, ProductDetails::Generic);
+        assert!(dns.product_details.blocks().is_empty());
+    }
+
+    #[test]
+    fn test_policy_element_strings() {
+        let principal: Value = serde_json::json!({"AWS": ["arn:aws:iam::444455556666:root", "555566667777"]});
+
+        assert_eq!(strings(&principal), vec!["arn:aws:iam::444455556666:root", "555566667777"]);
+        assert_eq!(strings(&Value::String("*".to_string())), vec!["*"]);
+    }
+}
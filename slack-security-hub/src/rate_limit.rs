@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// Token bucket refilled continuously at `refill_per_sec` up to `capacity` tokens.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(capacity: f64, refill_per_sec: f64, now: Instant) -> Self {
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec,
+            last_refill: now,
+        }
+    }
+
+    /// Takes a token, or returns how long to wait until one is available.
+    pub fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / self.refill_per_sec))
+        }
+    }
+}
+
+/// Keyed token buckets, used to respect Slack's per-channel posting limit. Keys can be
+/// aliased, so that a channel addressed by name and by ID shares one bucket.
+#[derive(Debug)]
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+    aliases: Mutex<HashMap<String, String>>,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_sec,
+            buckets: Mutex::new(HashMap::new()),
+            aliases: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Makes `key` share the bucket of `canonical`. The tokens already taken under `key`
+    /// carry over when `canonical` has no bucket yet.
+    pub fn alias(&self, key: &str, canonical: &str) {
+        if key == canonical {
+            return;
+        }
+        self.aliases.lock().unwrap().insert(key.to_string(), canonical.to_string());
+
+        let mut buckets = self.buckets.lock().unwrap();
+        if let Some(bucket) = buckets.remove(key) {
+            buckets.entry(canonical.to_string()).or_insert(bucket);
+        }
+    }
+
+    /// Waits until a token is available in the bucket of `key`.
+    pub async fn acquire(&self, key: &str) {
+        let key = self.aliases.lock().unwrap().get(key).cloned().unwrap_or_else(|| key.to_string());
+        loop {
+            let wait = {
+                let mut buckets = self.buckets.lock().unwrap();
+                buckets
+                    .entry(key.clone())
+                    .or_insert_with(|| TokenBucket::new(self.capacity, self.refill_per_sec, Instant::now()))
+                    .try_acquire(Instant::now())
+            };
+
+            match wait {
+                Ok(()) => return,
+                Err(delay) => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_refills_over_time() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 1.0, start);
+
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        assert_eq!(bucket.try_acquire(start), Err(Duration::from_secs(1)));
+
+        let later = start + Duration::from_millis(500);
+        assert_eq!(bucket.try_acquire(later), Err(Duration::from_millis(500)));
+        assert!(bucket.try_acquire(start + Duration::from_secs(1)).is_ok());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_limiter_is_per_key() {
+        let limiter = RateLimiter::new(1.0, 1.0);
+        let start = Instant::now();
+
+        limiter.acquire("#sec-incidents").await;
+        limiter.acquire("#sec-vuln-backlog").await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+
+        limiter.acquire("#sec-incidents").await;
+        limiter.acquire("#sec-incidents").await;
+        assert!(start.elapsed() >= Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_aliased_keys_share_a_bucket() {
+        let limiter = RateLimiter::new(1.0, 1.0);
+        let start = Instant::now();
+
+        limiter.acquire("#sec-incidents").await;
+        limiter.alias("#sec-incidents", "C0123456789");
+        limiter.acquire("C0123456789").await;
+        assert!(start.elapsed() >= Duration::from_secs(1));
+
+        limiter.acquire("#sec-incidents").await;
+        assert!(start.elapsed() >= Duration::from_secs(2));
+    }
+}
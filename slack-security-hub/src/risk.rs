@@ -0,0 +1,282 @@
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use crate::products::ProductDetails;
+use crate::severity::Severity;
+use crate::struct_event::FindingSummary;
+
+/// Highest risk score; scores are clamped to `0..=MAX_RISK_SCORE`.
+pub const MAX_RISK_SCORE: u32 = 100;
+
+/// Points each risk factor adds to (or removes from) the score of a finding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskModel {
+    /// Base points per severity. Severities without an entry score 0.
+    #[serde(default = "default_severity_points")]
+    pub severity: HashMap<Severity, i32>,
+    /// Tag names holding the environment, compared case-insensitively. Resource tags
+    /// are used first, then the account tags.
+    #[serde(default = "default_environment_tags")]
+    pub environment_tags: Vec<String>,
+    /// Environment tag value (lowercase) to points, e.g. `prod` to 20.
+    #[serde(default = "default_environments")]
+    pub environments: HashMap<String, i32>,
+    /// Points when a resource has a public IP or is publicly accessible.
+    #[serde(default = "default_public_exposure")]
+    pub public_exposure: i32,
+    /// Points when every connection in the evidence was blocked.
+    #[serde(default = "default_blocked")]
+    pub blocked: i32,
+    /// Account ID to the points of its criticality.
+    #[serde(default)]
+    pub account_criticality: HashMap<String, i32>,
+}
+
+fn default_severity_points() -> HashMap<Severity, i32> {
+    HashMap::from([
+        (Severity::Low, 10),
+        (Severity::Medium, 30),
+        (Severity::High, 50),
+        (Severity::Critical, 70),
+        (Severity::Fatal, 80),
+    ])
+}
+
+fn default_environment_tags() -> Vec<String> {
+    vec!["env".to_string(), "environment".to_string()]
+}
+
+fn default_environments() -> HashMap<String, i32> {
+    HashMap::from([
+        ("prod".to_string(), 20),
+        ("production".to_string(), 20),
+        ("staging".to_string(), 5),
+        ("dev".to_string(), -10),
+        ("sandbox".to_string(), -20),
+    ])
+}
+
+fn default_public_exposure() -> i32 {
+    15
+}
+
+fn default_blocked() -> i32 {
+    -20
+}
+
+impl Default for RiskModel {
+    fn default() -> Self {
+        Self {
+            severity: default_severity_points(),
+            environment_tags: default_environment_tags(),
+            environments: default_environments(),
+            public_exposure: default_public_exposure(),
+            blocked: default_blocked(),
+            account_criticality: HashMap::new(),
+        }
+    }
+}
+
+/// Risk score of a finding with the factors that contributed to it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskScore {
+    pub score: u32,
+    pub factors: Vec<RiskFactor>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskFactor {
+    /// Short description, e.g. `env=prod`.
+    pub name: String,
+    pub points: i32,
+}
+
+impl RiskModel {
+    /// Loads the model from `RISK_SCORING` (inline JSON) or `RISK_SCORING_FILE`. Without
+    /// either variable the default points are used.
+    pub fn from_env() -> Result<Self, Error> {
+        let json = if let Ok(json) = std::env::var("RISK_SCORING") {
+            json
+        } else if let Ok(path) = std::env::var("RISK_SCORING_FILE") {
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read risk scoring file '{}': {}", path, e))?
+        } else {
+            return Ok(Self::default());
+        };
+
+        Self::from_json(&json)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let mut model: Self = serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse risk scoring: {}", e))?;
+        model.environments = model.environments.into_iter()
+            .map(|(environment, points)| (environment.to_ascii_lowercase(), points))
+            .collect();
+        Ok(model)
+    }
+
+    /// Scores the finding. Account tags are only used once the account enricher has
+    /// filled in `account_info`.
+    pub fn score(&self, summary: &FindingSummary) -> RiskScore {
+        let mut factors = Vec::new();
+        let mut add = |name: String, points: i32| {
+            if points != 0 {
+                factors.push(RiskFactor { name, points });
+            }
+        };
+
+        add(
+            format!("{} severity", summary.severity),
+            self.severity.get(&summary.severity).copied().unwrap_or(0),
+        );
+
+        if let Some((tag, environment)) = self.environment(summary) {
+            let points = self.environments.get(&environment.to_ascii_lowercase()).copied().unwrap_or(0);
+            add(format!("{}={}", tag, environment), points);
+        }
+
+        if let Some(exposure) = public_exposure(summary) {
+            add(exposure, self.public_exposure);
+        }
+
+        if summary.indicators.blocked == Some(true) {
+            add("Traffic blocked".to_string(), self.blocked);
+        }
+
+        if let Some(points) = self.account_criticality.get(&summary.account) {
+            add("Critical account".to_string(), *points);
+        }
+
+        let total: i32 = factors.iter().map(|f| f.points).sum();
+        RiskScore {
+            score: total.clamp(0, MAX_RISK_SCORE as i32) as u32,
+            factors,
+        }
+    }
+
+    /// First environment tag of the resources, falling back to the account tags.
+    fn environment<'a>(&self, summary: &'a FindingSummary) -> Option<(&'a str, &'a str)> {
+        let is_environment_tag = |name: &str| self.environment_tags.iter().any(|t| t.eq_ignore_ascii_case(name));
+
+        summary.resource_tags.iter()
+            .find(|(name, _)| is_environment_tag(name))
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .or_else(|| {
+                summary.account_info.as_ref()?.tags.iter()
+                    .find(|(name, _)| is_environment_tag(name))
+                    .map(|(name, value)| (name.as_str(), value.as_str()))
+            })
+    }
+}
+
+impl RiskScore {
+    /// Factors as `env=prod +20`, in the order they were evaluated.
+    pub fn factors_text(&self) -> String {
+        self.factors.iter()
+            .map(|f| format!("{} {:+}", f.name, f.points))
+            .collect::<Vec<_>>()
+            .join(" · ")
+    }
+}
+
+/// Describes why the finding is publicly exposed, `None` when it is not.
+fn public_exposure(summary: &FindingSummary) -> Option<String> {
+    let public_ip = summary.resources.iter()
+        .flat_map(|r| &r.network_interfaces)
+        .filter_map(|ni| ni.ip.as_deref())
+        .find(|ip| ip.parse().is_ok_and(is_public_ip));
+    if let Some(ip) = public_ip {
+        return Some(format!("Public IP {}", ip));
+    }
+
+    match &summary.product_details {
+        ProductDetails::AccessAnalyzer(details) if details.is_public == Some(true) => Some("Public access".to_string()),
+        _ => None,
+    }
+}
+
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            // 100.64.0.0/10 is carrier-grade NAT space
+            let shared = a == 100 && (b & 0xc0) == 64;
+            !(ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified() || shared)
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            // fc00::/7 unique local and fe80::/10 link-local addresses
+            let local = (first & 0xfe00) == 0xfc00 || (first & 0xffc0) == 0xfe80;
+            !(ip.is_loopback() || ip.is_unspecified() || local)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::AccountInfo;
+    use crate::struct_event::{test_support::summary, NetworkInterfaceSummary};
+
+    #[test]
+    fn test_default_score_combines_factors() {
+        let mut summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        summary.resources[0].network_interfaces.push(NetworkInterfaceSummary {
+            ip: Some("203.0.113.10".to_string()),
+            ..Default::default()
+        });
+        let risk = RiskModel::default().score(&summary);
+
+        assert_eq!(risk.factors, vec![
+            RiskFactor { name: "Critical severity".to_string(), points: 70 },
+            RiskFactor { name: "env=prod".to_string(), points: 20 },
+            RiskFactor { name: "Public IP 203.0.113.10".to_string(), points: 15 },
+        ]);
+        assert_eq!(risk.score, 100);
+        assert_eq!(risk.factors_text(), "Critical severity +70 · env=prod +20 · Public IP 203.0.113.10 +15");
+    }
+
+    #[test]
+    fn test_configured_model() {
+        let model = RiskModel::from_json(r#"{
+            "environments": { "Dev": 0 },
+            "account_criticality": { "222222222222": 25 }
+        }"#).unwrap();
+        let summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        let risk = model.score(&summary);
+
+        // Points that are not configured keep their defaults, zero points are not listed
+        assert_eq!(risk.factors, vec![
+            RiskFactor { name: "High severity".to_string(), points: 50 },
+            RiskFactor { name: "Critical account".to_string(), points: 25 },
+        ]);
+        assert_eq!(risk.score, 75);
+    }
+
+    #[test]
+    fn test_account_tags_and_blocked_traffic() {
+        let mut summary = summary(include_str!("../fixtures/guardduty_api_call.json"));
+        summary.resource_tags.clear();
+        summary.account_info = Some(AccountInfo {
+            tags: [("Environment".to_string(), "sandbox".to_string())].into(),
+            ..Default::default()
+        });
+        summary.indicators.blocked = Some(true);
+        let risk = RiskModel::default().score(&summary);
+
+        assert!(risk.factors.contains(&RiskFactor { name: "Environment=sandbox".to_string(), points: -20 }));
+        assert!(risk.factors.contains(&RiskFactor { name: "Traffic blocked".to_string(), points: -20 }));
+    }
+
+    #[test]
+    fn test_public_ips() {
+        for ip in ["10.0.1.25", "172.16.4.2", "192.168.0.1", "100.64.1.1", "169.254.169.254", "fd00::1", "fe80::1"] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{} is not public", ip);
+        }
+        for ip in ["198.51.100.23", "52.94.76.1", "2600:1f18::1"] {
+            assert!(is_public_ip(ip.parse().unwrap()), "{} is public", ip);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::compliance::FindingCategory;
+use crate::severity::Severity;
+use crate::struct_event::FindingSummary;
+
+/// Routing configuration that decides which Slack channels receive a finding.
+///
+/// Every route whose criteria match contributes its channels, in order, until a
+/// matching route with `stop` set is reached. When no route matches the finding
+/// is sent to `default_channels`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RoutingRules {
+    #[serde(default)]
+    pub default_channels: Vec<String>,
+    #[serde(default)]
+    pub routes: Vec<Route>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Route {
+    pub name: String,
+    pub channels: Vec<String>,
+    #[serde(default, rename = "match")]
+    pub criteria: RouteMatch,
+    #[serde(default)]
+    pub stop: bool,
+}
+
+/// Criteria of a route. Empty lists match anything; all non-empty criteria must match.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RouteMatch {
+    #[serde(default)]
+    pub severities: Vec<Severity>,
+    #[serde(default)]
+    pub min_severity: Option<Severity>,
+    /// Minimum risk score. Findings without a score never match.
+    #[serde(default)]
+    pub min_risk_score: Option<u32>,
+    #[serde(default)]
+    pub products: Vec<String>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    #[serde(default)]
+    pub regions: Vec<String>,
+    #[serde(default)]
+    pub resource_types: Vec<String>,
+    /// Tag name to required value. A value of `*` only requires the tag to be present.
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// Finding categories, e.g. `compliance` for control failures or `threat` for detections.
+    #[serde(default)]
+    pub categories: Vec<FindingCategory>,
+    /// Security Hub CSPM control IDs such as `S3.8`.
+    #[serde(default)]
+    pub controls: Vec<String>,
+}
+
+impl RoutingRules {
+    /// Loads the rules from `ROUTING_RULES` (inline JSON) or `ROUTING_RULES_FILE` (path to
+    /// a JSON file). `SLACK_CHANNEL` is used as the fallback route when the configuration
+    /// does not define `default_channels`; one of them is required.
+    pub fn from_env() -> Result<Self, Error> {
+        let mut rules = if let Ok(json) = std::env::var("ROUTING_RULES") {
+            Self::from_json(&json)?
+        } else if let Ok(path) = std::env::var("ROUTING_RULES_FILE") {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read routing rules file '{}': {}", path, e))?;
+            Self::from_json(&contents)?
+        } else {
+            Self::default()
+        };
+
+        if rules.default_channels.is_empty()
+            && let Ok(channel) = std::env::var("SLACK_CHANNEL")
+        {
+            rules.default_channels.push(channel);
+        }
+
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Requires a default route, so that findings matching no route are still posted.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.default_channels.is_empty() {
+            return Err("No default channel configured: set default_channels in the routing rules or the SLACK_CHANNEL environment variable".into());
+        }
+        Ok(())
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json)
+            .map_err(|e| format!("Failed to parse routing rules: {}", e).into())
+    }
+
+    /// Returns the de-duplicated list of channels the finding should be posted to.
+    pub fn channels_for(&self, summary: &FindingSummary) -> Vec<String> {
+        let mut channels: Vec<String> = Vec::new();
+
+        for route in &self.routes {
+            if !route.criteria.matches(summary) {
+                continue;
+            }
+            for channel in &route.channels {
+                if !channels.contains(channel) {
+                    channels.push(channel.clone());
+                }
+            }
+            if route.stop {
+                break;
+            }
+        }
+
+        if channels.is_empty() {
+            channels = self.default_channels.clone();
+        }
+
+        channels
+    }
+}
+
+impl RouteMatch {
+    pub fn matches(&self, summary: &FindingSummary) -> bool {
+        matches_any(&self.severities, |s| *s == summary.severity)
+            && self.min_severity.is_none_or(|min| summary.severity >= min)
+            && self.min_risk_score.is_none_or(|min| summary.risk.as_ref().is_some_and(|r| r.score >= min))
+            && matches_any(&self.products, |p| p.eq_ignore_ascii_case(&summary.product_name))
+            && matches_any(&self.accounts, |a| a == &summary.account)
+            && matches_any(&self.regions, |r| r == &summary.region)
+            && matches_any(&self.resource_types, |t| {
+                summary.resource_types.iter().any(|rt| rt.eq_ignore_ascii_case(t))
+            })
+            && self.tags.iter().all(|(name, value)| {
+                summary.resource_tags.iter().any(|(tag_name, tag_value)| {
+                    tag_name == name && (value == "*" || tag_value == value)
+                })
+            })
+            && matches_any(&self.categories, |c| *c == summary.category)
+            && matches_any(&self.controls, |c| {
+                summary.control.as_ref().is_some_and(|control| control.control_id.eq_ignore_ascii_case(c))
+            })
+    }
+}
+
+pub(crate) fn matches_any<T, F>(values: &[T], predicate: F) -> bool
+where
+    F: Fn(&T) -> bool,
+{
+    values.is_empty() || values.iter().any(predicate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::risk::RiskScore;
+    use crate::struct_event::test_support::summary;
+
+    const RULES: &str = r##"{
+        "default_channels": ["#aws-security"],
+        "routes": [
+            {
+                "name": "prod-criticals",
+                "channels": ["#sec-incidents"],
+                "match": { "severities": ["Critical"], "accounts": ["111111111111"] }
+            },
+            {
+                "name": "dev-inspector",
+                "channels": ["#sec-vuln-backlog"],
+                "match": { "products": ["inspector"], "tags": { "env": "dev" } },
+                "stop": true
+            },
+            {
+                "name": "all-ec2",
+                "channels": ["#sec-ec2"],
+                "match": { "resource_types": ["AWS::EC2::Instance"], "regions": ["us-east-1"] }
+            }
+        ]
+    }"##;
+
+    #[test]
+    fn test_prod_critical_fans_out_to_matching_routes() {
+        let rules = RoutingRules::from_json(RULES).unwrap();
+        let summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+
+        assert_eq!(rules.channels_for(&summary), vec!["#sec-incidents", "#sec-ec2"]);
+    }
+
+    #[test]
+    fn test_dev_inspector_goes_to_backlog() {
+        let rules = RoutingRules::from_json(RULES).unwrap();
+        let summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+
+        assert_eq!(rules.channels_for(&summary), vec!["#sec-vuln-backlog"]);
+    }
+
+    #[test]
+    fn test_unmatched_finding_uses_default_route() {
+        let rules = RoutingRules::from_json(RULES).unwrap();
+        let mut summary = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        summary.resource_tags = vec![("env".to_string(), "staging".to_string())];
+
+        assert_eq!(rules.channels_for(&summary), vec!["#aws-security"]);
+    }
+
+    #[test]
+    fn test_wildcard_tag_requires_presence() {
+        let criteria = RouteMatch {
+            tags: HashMap::from([("team".to_string(), "*".to_string())]),
+            ..Default::default()
+        };
+        let prod = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let dev = summary(include_str!("../fixtures/inspector_dev_high.json"));
+
+        assert!(criteria.matches(&prod));
+        assert!(!criteria.matches(&dev));
+    }
+
+    #[test]
+    fn test_min_severity_threshold() {
+        let criteria = RouteMatch {
+            min_severity: Some(Severity::Critical),
+            ..Default::default()
+        };
+        let prod = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        let dev = summary(include_str!("../fixtures/inspector_dev_high.json"));
+
+        assert!(criteria.matches(&prod));
+        assert!(!criteria.matches(&dev));
+    }
+
+    #[test]
+    fn test_control_failures_route_separately_from_threats() {
+        let rules = RoutingRules::from_json(r##"{
+            "default_channels": ["#aws-security"],
+            "routes": [
+                { "name": "controls", "channels": ["#sec-compliance"], "match": { "categories": ["compliance"] } },
+                { "name": "threats", "channels": ["#sec-threats"], "match": { "categories": ["threat"] } }
+            ]
+        }"##).unwrap();
+        let control = summary(include_str!("../fixtures/cspm_s3_control_failed.json"));
+        let threat = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+
+        assert_eq!(rules.channels_for(&control), vec!["#sec-compliance"]);
+        assert_eq!(rules.channels_for(&threat), vec!["#sec-threats"]);
+        assert!(RouteMatch { controls: vec!["s3.8".to_string()], ..Default::default() }.matches(&control));
+        assert!(!RouteMatch { controls: vec!["S3.8".to_string()], ..Default::default() }.matches(&threat));
+    }
+
+    #[test]
+    fn test_min_risk_score() {
+        let criteria = RouteMatch { min_risk_score: Some(80), ..Default::default() };
+        let mut summary = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+
+        assert!(!criteria.matches(&summary));
+        summary.risk = Some(RiskScore { score: 85, ..Default::default() });
+        assert!(criteria.matches(&summary));
+        summary.risk = Some(RiskScore { score: 60, ..Default::default() });
+        assert!(!criteria.matches(&summary));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!(RoutingRules::from_json("{\"routes\": [{\"name\": \"x\"}]}").is_err());
+    }
+
+    #[test]
+    fn test_rules_without_default_route_are_rejected() {
+        let rules = RoutingRules::from_json(r##"{
+            "routes": [{"name": "prod", "channels": ["#sec-incidents"], "match": {"accounts": ["111111111111"]}}]
+        }"##).unwrap();
+        assert!(rules.validate().is_err());
+
+        // Without a default the unmatched finding would have nowhere to go
+        let dev = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        assert!(rules.channels_for(&dev).is_empty());
+        assert!(RoutingRules::from_json(RULES).unwrap().validate().is_ok());
+    }
+}
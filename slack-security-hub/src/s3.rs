@@ -0,0 +1,76 @@
+use aws_sdk_s3::Client;
+use lambda_runtime::Error;
+
+/// Bucket and key (or key prefix) of an `s3://bucket/key` URI from the configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct S3Uri {
+    pub bucket: String,
+    pub key: String,
+}
+
+impl S3Uri {
+    /// Parses the location of a single object, `s3://bucket/key`.
+    pub fn object(uri: &str) -> Result<Self, String> {
+        match Self::parse(uri) {
+            Some(location) if !location.key.is_empty() => Ok(location),
+            _ => Err("expected s3://bucket/key".to_string()),
+        }
+    }
+
+    /// Parses the location of a key prefix, `s3://bucket/prefix`. The prefix may be empty
+    /// and is returned without its trailing slashes.
+    pub fn prefix(uri: &str) -> Result<Self, String> {
+        match Self::parse(uri) {
+            Some(location) => Ok(Self {
+                key: location.key.trim_end_matches('/').to_string(),
+                ..location
+            }),
+            None => Err("expected s3://bucket/prefix".to_string()),
+        }
+    }
+
+    fn parse(uri: &str) -> Option<Self> {
+        let rest = uri.strip_prefix("s3://")?;
+        let (bucket, key) = rest.split_once('/').unwrap_or((rest, ""));
+        if bucket.is_empty() {
+            return None;
+        }
+        Some(Self {
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+/// Reads the whole object at `key`, or `None` when it does not exist.
+pub async fn get_object(client: &Client, bucket: &str, key: &str) -> Result<Option<Vec<u8>>, Error> {
+    let object = match client.get_object().bucket(bucket).key(key).send().await {
+        Ok(object) => object,
+        Err(e) if e.as_service_error().is_some_and(|e| e.is_no_such_key()) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    Ok(Some(object.body.collect().await?.into_bytes().to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_object_uri() {
+        assert_eq!(
+            S3Uri::object("s3://config-bucket/security/suppressions.yaml").unwrap(),
+            S3Uri { bucket: "config-bucket".to_string(), key: "security/suppressions.yaml".to_string() }
+        );
+        assert!(S3Uri::object("s3://config-bucket").is_err());
+        assert!(S3Uri::object("s3://config-bucket/").is_err());
+        assert!(S3Uri::object("https://config-bucket/key").is_err());
+    }
+
+    #[test]
+    fn test_prefix_uri() {
+        assert_eq!(S3Uri::prefix("s3://my-bucket/dead-letters/").unwrap().key, "dead-letters");
+        assert_eq!(S3Uri::prefix("s3://my-bucket").unwrap().key, "");
+        assert!(S3Uri::prefix("s3:///dead-letters").is_err());
+    }
+}
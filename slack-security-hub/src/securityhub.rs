@@ -0,0 +1,163 @@
+use aws_config::SdkConfig;
+use aws_sdk_securityhub::types::{AwsSecurityFindingIdentifier, NoteUpdate, WorkflowStatus, WorkflowUpdate};
+use aws_sdk_securityhub::Client;
+use lambda_runtime::{tracing, Error};
+use serde::{Deserialize, Serialize};
+use crate::struct_event::FindingSummary;
+
+/// BatchUpdateFindings and BatchUpdateFindingsV2 accept at most 100 findings per call.
+const MAX_FINDINGS_PER_UPDATE: usize = 100;
+
+/// Notes longer than this are rejected by Security Hub.
+const MAX_NOTE_LENGTH: usize = 512;
+
+pub const NOTE_UPDATED_BY: &str = "security-hub-to-slack";
+
+/// Identifies a finding in Security Hub updates. The workflow buttons of finding messages
+/// carry it as JSON in their `value`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FindingIdentifier {
+    /// OCSF finding, updated by `metadata.uid` with BatchUpdateFindingsV2.
+    Ocsf { metadata_uid: String },
+    /// Finding from a legacy ASFF event, updated by `Id` and `ProductArn` with
+    /// BatchUpdateFindings.
+    Asff { id: String, product_arn: String },
+}
+
+/// OCSF `status_id` matching a workflow status, as taken by BatchUpdateFindingsV2.
+fn ocsf_status_id(status: &WorkflowStatus) -> i32 {
+    match status {
+        WorkflowStatus::New => 1,
+        WorkflowStatus::Notified => 2,
+        WorkflowStatus::Suppressed => 3,
+        WorkflowStatus::Resolved => 4,
+        _ => 99,
+    }
+}
+
+/// Whether the latest status change of the finding was made with a workflow button in
+/// Slack. `slack-event` records it with a note such as `Resolved by alice in Slack`, and
+/// has already updated the message by the time the change comes back from Security Hub.
+pub fn changed_in_slack(summary: &FindingSummary) -> bool {
+    let Some((verb, _)) = summary.comment.as_deref()
+        .and_then(|comment| comment.strip_suffix(" in Slack"))
+        .and_then(|action| action.split_once(" by "))
+    else {
+        return false;
+    };
+
+    // A later change made elsewhere keeps the note, but not the status the button set
+    let statuses: &[&str] = match verb {
+        "Acknowledged" => &["In Progress", "Notified"],
+        "Suppressed" => &["Suppressed"],
+        "Resolved" => &["Resolved"],
+        _ => return false,
+    };
+    statuses.iter().any(|status| summary.status.eq_ignore_ascii_case(status))
+}
+
+/// Updates findings in Security Hub.
+pub struct SecurityHubClient {
+    client: Client,
+}
+
+impl SecurityHubClient {
+    pub fn new(config: &SdkConfig) -> Self {
+        Self {
+            client: Client::new(config),
+        }
+    }
+
+    /// Sets the workflow status of the findings and records `note` on them. Findings that
+    /// Security Hub could not update are logged and reported in the error.
+    pub async fn set_workflow_status(
+        &self,
+        findings: &[FindingIdentifier],
+        status: WorkflowStatus,
+        note: &str,
+    ) -> Result<(), Error> {
+        let note: String = note.chars().take(MAX_NOTE_LENGTH).collect();
+
+        let mut metadata_uids = Vec::new();
+        let mut asff_identifiers = Vec::new();
+        for finding in findings {
+            match finding {
+                FindingIdentifier::Ocsf { metadata_uid } => metadata_uids.push(metadata_uid.clone()),
+                FindingIdentifier::Asff { id, product_arn } => asff_identifiers.push(
+                    AwsSecurityFindingIdentifier::builder().id(id).product_arn(product_arn).build(),
+                ),
+            }
+        }
+
+        let mut failed = 0;
+
+        for chunk in metadata_uids.chunks(MAX_FINDINGS_PER_UPDATE) {
+            let output = self.client
+                .batch_update_findings_v2()
+                .set_metadata_uids(Some(chunk.to_vec()))
+                .status_id(ocsf_status_id(&status))
+                .comment(&note)
+                .send()
+                .await?;
+
+            for unprocessed in output.unprocessed_findings() {
+                failed += 1;
+                tracing::error!(
+                    "Security Hub did not update finding {}: {}",
+                    unprocessed.metadata_uid().unwrap_or("unknown"),
+                    unprocessed.error_message().unwrap_or("unknown error"),
+                );
+            }
+        }
+
+        for chunk in asff_identifiers.chunks(MAX_FINDINGS_PER_UPDATE) {
+            let output = self.client
+                .batch_update_findings()
+                .set_finding_identifiers(Some(chunk.to_vec()))
+                .workflow(WorkflowUpdate::builder().status(status.clone()).build())
+                .note(NoteUpdate::builder().text(&note).updated_by(NOTE_UPDATED_BY).build())
+                .send()
+                .await?;
+
+            for unprocessed in output.unprocessed_findings() {
+                failed += 1;
+                tracing::error!(
+                    "Security Hub did not update finding {}: {}",
+                    unprocessed.finding_identifier().and_then(|f| f.id()).unwrap_or("unknown"),
+                    unprocessed.error_message().unwrap_or("unknown error"),
+                );
+            }
+        }
+
+        if failed > 0 {
+            return Err(format!("{} of {} findings were not updated", failed, findings.len()).into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support;
+
+    fn summary(status: &str, comment: Option<&str>) -> FindingSummary {
+        let mut summary = test_support::summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+        summary.status = status.to_string();
+        summary.comment = comment.map(str::to_string);
+        summary
+    }
+
+    #[test]
+    fn test_changed_in_slack() {
+        assert!(changed_in_slack(&summary("Resolved", Some("Resolved by alice in Slack"))));
+        assert!(changed_in_slack(&summary("In Progress", Some("Acknowledged by alice in Slack"))));
+        assert!(changed_in_slack(&summary("Notified", Some("Acknowledged by alice in Slack"))));
+
+        // Reopened in the console after being resolved from Slack
+        assert!(!changed_in_slack(&summary("New", Some("Resolved by alice in Slack"))));
+        assert!(!changed_in_slack(&summary("Suppressed", Some("Suppressed by rule 'dev': noisy"))));
+        assert!(!changed_in_slack(&summary("Resolved", None)));
+    }
+}
@@ -0,0 +1,204 @@
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// OCSF severity of a finding, ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Severity {
+    Unknown,
+    Informational,
+    Low,
+    Medium,
+    High,
+    Critical,
+    Fatal,
+}
+
+impl Severity {
+    /// Resolves the severity of a finding from its label, falling back to the OCSF `severity_id`.
+    pub fn from_finding(label: Option<&str>, severity_id: Option<i32>) -> Self {
+        label
+            .and_then(|l| l.parse().ok())
+            .or_else(|| severity_id.map(Self::from_id))
+            .unwrap_or(Severity::Unknown)
+    }
+
+    pub fn from_id(id: i32) -> Self {
+        match id {
+            1 => Severity::Informational,
+            2 => Severity::Low,
+            3 => Severity::Medium,
+            4 => Severity::High,
+            5 => Severity::Critical,
+            6 => Severity::Fatal,
+            _ => Severity::Unknown,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Unknown => "Unknown",
+            Severity::Informational => "Informational",
+            Severity::Low => "Low",
+            Severity::Medium => "Medium",
+            Severity::High => "High",
+            Severity::Critical => "Critical",
+            Severity::Fatal => "Fatal",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "unknown" | "other" => Ok(Severity::Unknown),
+            "informational" | "info" => Ok(Severity::Informational),
+            "low" => Ok(Severity::Low),
+            "medium" => Ok(Severity::Medium),
+            "high" => Ok(Severity::High),
+            "critical" => Ok(Severity::Critical),
+            "fatal" => Ok(Severity::Fatal),
+            other => Err(format!("Unknown severity: {}", other)),
+        }
+    }
+}
+
+impl TryFrom<String> for Severity {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Severity> for String {
+    fn from(severity: Severity) -> Self {
+        severity.as_str().to_string()
+    }
+}
+
+/// Decides which severities are posted, configured through `SEVERITY_FILTER`.
+///
+/// Accepts a threshold (`>=High`) or a comma-separated allow-list (`Critical,Medium`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SeverityFilter {
+    #[default]
+    All,
+    AtLeast(Severity),
+    AnyOf(Vec<Severity>),
+}
+
+impl SeverityFilter {
+    pub fn from_env() -> Result<Self, Error> {
+        match std::env::var("SEVERITY_FILTER") {
+            Ok(value) => value
+                .parse()
+                .map_err(|e| format!("Invalid SEVERITY_FILTER '{}': {}", value, e).into()),
+            Err(_) => Ok(SeverityFilter::All),
+        }
+    }
+
+    pub fn allows(&self, severity: Severity) -> bool {
+        match self {
+            SeverityFilter::All => true,
+            SeverityFilter::AtLeast(threshold) => severity >= *threshold,
+            SeverityFilter::AnyOf(allowed) => allowed.contains(&severity),
+        }
+    }
+}
+
+impl FromStr for SeverityFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(SeverityFilter::All);
+        }
+
+        if let Some(threshold) = s.strip_prefix(">=") {
+            return Ok(SeverityFilter::AtLeast(threshold.parse()?));
+        }
+
+        let allowed = s
+            .split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Severity>, _>>()?;
+        // An empty allow-list would silently drop every finding
+        if allowed.is_empty() {
+            return Err("no severity in the allow-list".to_string());
+        }
+
+        Ok(SeverityFilter::AnyOf(allowed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_ordering() {
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::High > Severity::Medium);
+        assert!(Severity::Informational > Severity::Unknown);
+        assert!(Severity::Fatal > Severity::Critical);
+    }
+
+    #[test]
+    fn test_from_finding_prefers_label_then_id() {
+        assert_eq!(Severity::from_finding(Some("HIGH"), Some(2)), Severity::High);
+        assert_eq!(Severity::from_finding(None, Some(5)), Severity::Critical);
+        assert_eq!(Severity::from_finding(Some("bogus"), Some(3)), Severity::Medium);
+        assert_eq!(Severity::from_finding(None, None), Severity::Unknown);
+    }
+
+    #[test]
+    fn test_serde_round_trip() {
+        let severity: Severity = serde_json::from_str("\"critical\"").unwrap();
+        assert_eq!(severity, Severity::Critical);
+        assert_eq!(serde_json::to_string(&severity).unwrap(), "\"Critical\"");
+    }
+
+    #[test]
+    fn test_threshold_filter() {
+        let filter: SeverityFilter = ">=High".parse().unwrap();
+        assert_eq!(filter, SeverityFilter::AtLeast(Severity::High));
+        assert!(filter.allows(Severity::Critical));
+        assert!(filter.allows(Severity::High));
+        assert!(!filter.allows(Severity::Medium));
+    }
+
+    #[test]
+    fn test_allow_list_filter() {
+        let filter: SeverityFilter = "HIGH, CRITICAL".parse().unwrap();
+        assert!(filter.allows(Severity::High));
+        assert!(!filter.allows(Severity::Informational));
+
+        let filter: SeverityFilter = "Critical,Medium".parse().unwrap();
+        assert!(filter.allows(Severity::Medium));
+        assert!(!filter.allows(Severity::High));
+    }
+
+    #[test]
+    fn test_empty_and_invalid_filters() {
+        assert_eq!("".parse::<SeverityFilter>().unwrap(), SeverityFilter::All);
+        assert!(SeverityFilter::All.allows(Severity::Informational));
+        assert!(">=Severe".parse::<SeverityFilter>().is_err());
+        assert!("High,Urgent".parse::<SeverityFilter>().is_err());
+        assert!(",".parse::<SeverityFilter>().is_err());
+        assert!(" , ,".parse::<SeverityFilter>().is_err());
+        assert_eq!("High,".parse::<SeverityFilter>().unwrap(), SeverityFilter::AnyOf(vec![Severity::High]));
+    }
+}
@@ -1,50 +1,406 @@
-use reqwest::Client;
-use crate::struct_event::FindingSummary;
+use lambda_runtime::{tracing, Error};
+use rand::Rng;
+use reqwest::{Client, StatusCode};
+use crate::rate_limit::RateLimiter;
+use crate::accounts::AccountInfo;
+use crate::blockkit;
+use crate::icons::IconRegistry;
+use crate::indicators::{defang, Indicators};
+use crate::mentions::mention;
+use crate::risk::{RiskScore, MAX_RISK_SCORE};
+use crate::links::{self, Partition};
+use crate::struct_event::{FindingSummary, ResourceSummary};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::error::Error;
 use serde_json::json;
+use std::time::{Duration, SystemTime};
 
-pub async fn post_slack_message(
-    token: &str,
-    channel: &str,
-    summary: FindingSummary,
-) -> Result<(), Box<dyn Error>> {
-
-    let image_icon_url = match summary.product_name.as_str() {
-        "Inspector" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473027/Arch_Amazon-Inspector_64_mwcrkr.png",
-        "Macie" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473027/Arch_Amazon-Macie_64_fqdobr.png",
-        "WAF" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473046/Arch_AWS-WAF_64_sy685i.png",
-        "Shield" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473044/Arch_AWS-Shield_64_cgkrnf.png",
-        "GuardDuty" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473027/Arch_Amazon-GuardDuty_64_olhgt8.png",
-        "Detective" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473028/Arch_Amazon-Detective_64_c2ytyn.png",
-        "Config" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473312/Arch_AWS-Config_64_qmcyvc.png",
-        "IAM Access Analyzer" => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473035/Arch_AWS-Identity-and-Access-Management_64_twn9yu.png",
-        _ => "https://res.cloudinary.com/dgslmcpqb/image/upload/v1770473042/Arch_AWS-Security-Hub_64_r5hhru.png"
+pub const DEFAULT_API_URL: &str = "https://slack.com/api";
+
+/// Resources rendered in full before collapsing the rest into "+N more".
+pub const MAX_RENDERED_RESOURCES: usize = 5;
+
+/// Block and action IDs of the workflow buttons, matched by the `slack-event` function.
+pub const WORKFLOW_BLOCK_ID: &str = "finding_workflow";
+pub const ACKNOWLEDGE_ACTION_ID: &str = "acknowledge_finding";
+pub const SUPPRESS_ACTION_ID: &str = "suppress_finding";
+pub const RESOLVE_ACTION_ID: &str = "resolve_finding";
+
+/// Slack rejects button values longer than 2000 characters.
+const MAX_BUTTON_VALUE: usize = 2000;
+
+/// Coordinates of a posted Slack message, used to update it or reply in its thread.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SlackMessageRef {
+    pub channel: String,
+    pub ts: String,
+}
+
+/// Retry behaviour for rate-limited (HTTP 429, `ratelimited`) and 5xx responses.
+/// `max_delay` caps both the backoff and the `Retry-After` requested by Slack.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// Retries new messages after server and transport errors. Slack may have posted the
+    /// message anyway, so a retry can post it twice; turn it off to rather drop the message
+    /// to the dead-letter store.
+    pub retry_posts: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            retry_posts: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with full jitter for the given retry attempt (starting at 0).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self.base_delay.saturating_mul(2u32.saturating_pow(attempt));
+        let capped = exponential.min(self.max_delay);
+        let millis = capped.as_millis() as u64;
+        Duration::from_millis(rand::rng().random_range(0..=millis))
+    }
+}
+
+/// Time kept before the deadline of the invocation to record failed deliveries instead
+/// of waiting for another retry.
+const DEADLINE_MARGIN: Duration = Duration::from_secs(5);
+
+/// Slack Web API client. Messages are rate limited per channel (1 message per second,
+/// Slack's documented limit) for the lifetime of the client, and failed calls are retried.
+/// Channels addressed by name share the limit of the channel ID Slack resolves them to.
+pub struct SlackClient {
+    client: Client,
+    token: String,
+    api_url: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: RateLimiter,
+    icons: IconRegistry,
+    deadline: Option<SystemTime>,
+}
+
+/// Outcome of a single Slack Web API call.
+enum Attempt {
+    Done(Value),
+    Retry { after: Option<Duration>, reason: String },
+}
+
+impl SlackClient {
+    pub fn new(token: String) -> Self {
+        Self {
+            client: Client::new(),
+            token,
+            api_url: DEFAULT_API_URL.to_string(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: RateLimiter::new(1.0, 1.0),
+            icons: IconRegistry::default(),
+            deadline: None,
+        }
+    }
+
+    /// Creates a client honouring `SLACK_API_URL` (e.g. a local mock), `SLACK_MAX_RETRIES`,
+    /// `SLACK_RETRY_POSTS` and `ICON_BASE_URL`.
+    pub fn from_env(token: String) -> Result<Self, Error> {
+        let mut client = Self::new(token).with_icons(IconRegistry::from_env());
+
+        if let Ok(api_url) = std::env::var("SLACK_API_URL") {
+            client = client.with_api_url(api_url);
+        }
+
+        let mut retry_policy = RetryPolicy::default();
+        if let Ok(max_retries) = std::env::var("SLACK_MAX_RETRIES") {
+            retry_policy.max_retries = max_retries.trim().parse()
+                .map_err(|e| format!("Invalid SLACK_MAX_RETRIES '{}': {}", max_retries, e))?;
+        }
+        if let Ok(retry_posts) = std::env::var("SLACK_RETRY_POSTS") {
+            retry_policy.retry_posts = retry_posts.trim().parse()
+                .map_err(|e| format!("Invalid SLACK_RETRY_POSTS '{}': {}", retry_posts, e))?;
+        }
+
+        Ok(client.with_retry_policy(retry_policy))
+    }
+
+    pub fn with_api_url(mut self, api_url: impl Into<String>) -> Self {
+        self.api_url = api_url.into();
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_icons(mut self, icons: IconRegistry) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Stops retrying calls that would not complete before `deadline` (the deadline of the
+    /// Lambda invocation).
+    pub fn with_deadline(mut self, deadline: SystemTime) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    pub fn icons(&self) -> &IconRegistry {
+        &self.icons
+    }
+
+    pub async fn post_slack_message(
+        &self,
+        channel: &str,
+        summary: &FindingSummary,
+    ) -> Result<SlackMessageRef, Error> {
+        let final_json = json!(finding_blocks(summary, &self.icons));
+
+        match self.post_slack_message_with_blocks(channel, final_json).await {
+            Ok(message) => Ok(message),
+            Err(e) => Err(format!("Failed to post results message to Slack: {}", e).into()),
+        }
+    }
+
+    /// Re-renders a previously posted finding after a workflow status change and logs the
+    /// transition as a reply in the message thread.
+    pub async fn update_finding_status(
+        &self,
+        message: &SlackMessageRef,
+        summary: &FindingSummary,
+        previous_status: &str,
+    ) -> Result<(), Error> {
+        let final_json = json!(finding_blocks(summary, &self.icons));
+        self.update_slack_message_with_blocks(message, final_json).await
+            .map_err(|e| format!("Failed to update message in Slack: {}", e))?;
+
+        let text = format!(
+            "Status changed from *{}* to *{}* at {}",
+            previous_status,
+            summary.status,
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC"),
+        );
+        self.post_thread_reply(message, &text).await
+            .map_err(|e| format!("Failed to post status change to Slack thread: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Posts the full finding message as a reply in the thread of `parent`.
+    pub async fn post_finding_reply(
+        &self,
+        parent: &SlackMessageRef,
+        summary: &FindingSummary,
+    ) -> Result<SlackMessageRef, Error> {
+        let payload = serde_json::json!({
+            "channel": parent.channel,
+            "thread_ts": parent.ts,
+            "blocks": finding_blocks(summary, &self.icons)
+        });
+
+        let response_body = self.call_slack_api("chat.postMessage", &parent.channel, &payload).await?;
+        message_ref_from_response(&response_body)
+    }
+
+    pub async fn post_slack_message_with_blocks(
+        &self,
+        channel: &str,
+        all_blocks: Value,
+    ) -> Result<SlackMessageRef, Error> {
+        // Prepare JSON payload
+        let payload = serde_json::json!({
+            "channel": channel,
+            "blocks": all_blocks
+        });
+
+        let response_body = self.call_slack_api("chat.postMessage", channel, &payload).await?;
+        message_ref_from_response(&response_body)
+    }
+
+    pub async fn update_slack_message_with_blocks(
+        &self,
+        message: &SlackMessageRef,
+        all_blocks: Value,
+    ) -> Result<SlackMessageRef, Error> {
+        let payload = serde_json::json!({
+            "channel": message.channel,
+            "ts": message.ts,
+            "blocks": all_blocks
+        });
+
+        let response_body = self.call_slack_api("chat.update", &message.channel, &payload).await?;
+        message_ref_from_response(&response_body)
+    }
+
+    pub async fn post_thread_reply(
+        &self,
+        message: &SlackMessageRef,
+        text: &str,
+    ) -> Result<SlackMessageRef, Error> {
+        let payload = serde_json::json!({
+            "channel": message.channel,
+            "thread_ts": message.ts,
+            "text": text
+        });
+
+        let response_body = self.call_slack_api("chat.postMessage", &message.channel, &payload).await?;
+        message_ref_from_response(&response_body)
+    }
+
+    async fn call_slack_api(
+        &self,
+        method: &str,
+        channel: &str,
+        payload: &Value,
+    ) -> Result<Value, Error> {
+        let url = format!("{}/{}", self.api_url, method);
+        // A posted message may have been processed even when the response is lost or a
+        // server error, so retrying it can post it twice unless retries are turned off
+        let retry_unconfirmed = self.retry_policy.retry_posts || method != "chat.postMessage";
+        let mut attempt = 0;
+
+        loop {
+            self.rate_limiter.acquire(channel).await;
+
+            match self.send(&url, payload, retry_unconfirmed).await? {
+                Attempt::Done(response_body) => {
+                    if let Some(channel_id) = response_body["channel"].as_str() {
+                        self.rate_limiter.alias(channel, channel_id);
+                    }
+                    return Ok(response_body);
+                }
+                Attempt::Retry { after, reason } => {
+                    if attempt >= self.retry_policy.max_retries {
+                        return Err(format!("{} (gave up after {} retries)", reason, attempt).into());
+                    }
+
+                    let delay = after
+                        .map(|after| after.min(self.retry_policy.max_delay))
+                        .unwrap_or_else(|| self.retry_policy.backoff(attempt));
+                    if let Some(deadline) = self.deadline
+                        && SystemTime::now() + delay + DEADLINE_MARGIN > deadline
+                    {
+                        return Err(format!("{} (no time left to retry in {:?})", reason, delay).into());
+                    }
+                    tracing::warn!("Slack {} failed: {}, retrying in {:?}", method, reason, delay);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Sends one call. Rate-limited calls are always retried; calls that may have been
+    /// processed (transport and server errors) only when `retry_unconfirmed` is set.
+    async fn send(&self, url: &str, payload: &Value, retry_unconfirmed: bool) -> Result<Attempt, Error> {
+        // Make the POST request with JSON
+        let response = match self.client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", self.token))
+            .header("Content-Type", "application/json; charset=utf-8")
+            .json(payload)
+            .send()
+            .await
+        {
+            Ok(response) => response,
+            // Connection failures happen before the request is sent
+            Err(e) if retry_unconfirmed || e.is_connect() => {
+                return Ok(Attempt::Retry { after: None, reason: format!("Request error: {}", e) });
+            }
+            Err(e) => return Err(format!("Request error: {}", e).into()),
+        };
+
+        let retry_after = response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .map(Duration::from_secs);
+        let status = response.status();
+
+        // Check response status
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Ok(Attempt::Retry { after: retry_after, reason: format!("HTTP error: {}", status) });
+        }
+        if status.is_server_error() && retry_unconfirmed {
+            return Ok(Attempt::Retry { after: None, reason: format!("HTTP error: {}", status) });
+        }
+        if !status.is_success() {
+            return Err(format!("HTTP error: {}", status).into());
+        }
+
+        let response_body: Value = response.json().await?;
+        if response_body["ok"].as_bool().unwrap_or(false) {
+            return Ok(Attempt::Done(response_body));
+        }
+
+        let error = response_body["error"]
+            .as_str()
+            .unwrap_or("Unknown error");
+        if error == "ratelimited" {
+            Ok(Attempt::Retry { after: retry_after, reason: format!("Slack API error: {}", error) })
+        } else {
+            Err(format!("Slack API error: {}", error).into())
+        }
+    }
+}
+
+/// Returns true for workflow statuses that close a finding.
+pub fn is_closed_status(status: &str) -> bool {
+    status.eq_ignore_ascii_case("Resolved") || status.eq_ignore_ascii_case("Suppressed")
+}
+
+pub fn finding_blocks(summary: &FindingSummary, icons: &IconRegistry) -> Vec<Value> {
+
+    let image_icon_url = icons.icon_url(&summary.product_aws, &summary.product_name);
+
+    // Closed findings keep their message but show a strike-through title
+    let title_block = if is_closed_status(&summary.status) {
+        json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!("*~{}~*", blockkit::truncate(&summary.title, blockkit::MAX_HEADER_TEXT))
+            }
+        })
+    } else {
+        blockkit::header(&summary.title)
     };
 
     // Build the blocks for the Slack message
-    let mut blocks = vec![
-        json!({
-			"type": "header",
-			"text": {
-				"type": "plain_text",
-				"text": &summary.title,
-				"emoji": true
-			}
-		}),
-        json!({
-			"type": "section",
-			"text": {
-				"type": "mrkdwn",
-				"text": &summary.description
-			},
-			"accessory": {
-				"type": "image",
-				"image_url": image_icon_url,
-				"alt_text": "aws-service"
-			}
-		})
-    ];
+    let mut blocks = vec![title_block];
+
+    if let Some(status_text) = status_context(&summary.status) {
+        blocks.push(json!({
+            "type": "context",
+            "elements": [
+                {
+                    "type": "mrkdwn",
+                    "text": status_text
+                }
+            ]
+        }));
+    }
+
+    if let Some(control) = &summary.control {
+        blocks.push(control.badge_block());
+    }
+
+    // Long descriptions continue in additional sections instead of being rejected
+    let description = if summary.description.trim().is_empty() {
+        "_No description provided_"
+    } else {
+        summary.description.as_str()
+    };
+    blocks.extend(blockkit::mrkdwn_sections(description, Some(json!({
+        "type": "image",
+        "image_url": image_icon_url,
+        "alt_text": "aws-service"
+    }))));
 
     blocks.push(json!(
 		{
@@ -77,15 +433,11 @@ pub async fn post_slack_message(
                         },
 						{
 							"type": "text",
-							"text": format!("\n• Account: {}", &summary.account)
+							"text": format!("\n• Account: {}", summary.account_label())
 						},
                         {
                             "type": "text",
                             "text": format!("  |  Region: {}", &summary.region)
-                        },
-                        {
-                            "type": "text",
-                            "text": format!("  |  Resource Id: {}", &summary.resource_id)
                         }
 					]
 				}
@@ -93,6 +445,39 @@ pub async fn post_slack_message(
 		}
     ));
 
+    if let Some(risk) = &summary.risk {
+        blocks.push(risk_context_block(risk));
+    }
+
+    if let Some(block) = summary.account_info.as_ref().and_then(account_context_block) {
+        blocks.push(block);
+    }
+
+    if !summary.owners.is_empty() {
+        let mentions: Vec<String> = summary.owners.iter().map(|id| mention(id)).collect();
+        blocks.push(json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": format!(":bust_in_silhouette: *Owner:* {}", mentions.join(" "))
+            }
+        }));
+    }
+
+    blocks.extend(summary.product_details.blocks());
+
+    blocks.extend(resource_blocks(&summary.resources));
+
+    if let Some(block) = indicator_block(&summary.indicators) {
+        blocks.push(block);
+    }
+
+    blocks.push(console_actions(summary));
+
+    if let Some(block) = workflow_actions(summary) {
+        blocks.push(block);
+    }
+
     if summary.remediation != "no_remediation" {
         blocks.push(json!(
             {
@@ -121,50 +506,717 @@ pub async fn post_slack_message(
 		}
     ));
 
-    let final_json = json!(blocks);
+    blockkit::enforce_limits(blocks)
+}
+
+/// Context line with the risk score and the factors that contributed to it.
+pub fn risk_context_block(risk: &RiskScore) -> Value {
+    let mut text = format!(":dart: *Risk score: {}/{}*", risk.score, MAX_RISK_SCORE);
+    if !risk.factors.is_empty() {
+        text.push_str(&format!("  |  {}", risk.factors_text()));
+    }
+
+    json!({
+        "type": "context",
+        "elements": [
+            {
+                "type": "mrkdwn",
+                "text": text
+            }
+        ]
+    })
+}
 
-    match post_slack_message_with_blocks(token, channel, final_json).await {
-        Ok(_) => Ok(()),
-        Err(e) => Err(format!("Failed to post results message to Slack: {}", e).into()),
+/// Context line with the organizational unit path and tags of the account, `None` when
+/// neither is known.
+pub fn account_context_block(info: &AccountInfo) -> Option<Value> {
+    let mut parts = Vec::new();
+    if !info.ou_path.is_empty() {
+        parts.push(format!("OU: {}", info.ou_path.join(" / ")));
+    }
+    if !info.tags.is_empty() {
+        let tags: Vec<String> = info.tags.iter()
+            .map(|(key, value)| format!("`{}={}`", key, value))
+            .collect();
+        parts.push(format!("Account tags: {}", tags.join(" ")));
     }
+    if parts.is_empty() {
+        return None;
+    }
+
+    Some(json!({
+        "type": "context",
+        "elements": [
+            {
+                "type": "mrkdwn",
+                "text": parts.join("  |  ")
+            }
+        ]
+    }))
 }
 
+/// Renders every affected resource as a compact section, collapsing the ones past
+/// `MAX_RENDERED_RESOURCES` into a "+N more" context line.
+pub fn resource_blocks(resources: &[ResourceSummary]) -> Vec<Value> {
+    let mut blocks: Vec<Value> = resources.iter()
+        .take(MAX_RENDERED_RESOURCES)
+        .map(|resource| json!({
+            "type": "section",
+            "text": {
+                "type": "mrkdwn",
+                "text": resource_text(resource)
+            }
+        }))
+        .collect();
+
+    if resources.len() > MAX_RENDERED_RESOURCES {
+        blocks.push(json!({
+            "type": "context",
+            "elements": [
+                {
+                    "type": "mrkdwn",
+                    "text": format!("+{} more resources", resources.len() - MAX_RENDERED_RESOURCES)
+                }
+            ]
+        }));
+    }
+
+    blocks
+}
 
-pub async fn post_slack_message_with_blocks(
-    token: &str,
-    channel: &str,
-    all_blocks: Value,
-) -> Result<(), Box<dyn Error>> {
-    let client = Client::new();
-    let url = "https://slack.com/api/chat.postMessage";
+/// Buttons opening the finding in Security Hub, the console of the originating service
+/// and the first affected resource, all in the partition and region of the finding.
+pub fn console_actions(summary: &FindingSummary) -> Value {
+    let partition = Partition::resolve(Some(&summary.partition), &summary.region);
 
-    // Prepare JSON payload
-    let payload = serde_json::json!({
-        "channel": channel,
-        "blocks": all_blocks
+    let mut buttons = vec![link_button(
+        "Open in Security Hub",
+        "open_security_hub",
+        links::security_hub_finding_url(partition, &summary.region, &summary.uid),
+    )];
+    if let Some(url) = &summary.web_rule {
+        buttons.push(link_button(&format!("Open {}", summary.button_text), "open_service_console", url.clone()));
+    }
+    if let Some(url) = summary.control.as_ref().and_then(|c| c.docs_url()) {
+        buttons.push(link_button("Control remediation", "open_control_docs", url));
+    }
+    if let Some(resource) = summary.resources.first() {
+        let region = resource.region.as_deref().unwrap_or(&summary.region);
+        if let Some(url) = links::resource_url(partition, region, &resource.uid) {
+            buttons.push(link_button("View resource", "open_resource", url));
+        }
+    }
+
+    json!({
+        "type": "actions",
+        "elements": buttons
+    })
+}
+
+/// Acknowledge, Suppress and Resolve buttons handled by the `slack-event` function, which
+/// updates the workflow status of the finding in Security Hub. Closed findings get no
+/// buttons, and Acknowledge is only offered while the finding is new.
+pub fn workflow_actions(summary: &FindingSummary) -> Option<Value> {
+    if is_closed_status(&summary.status) {
+        return None;
+    }
+
+    let value = serde_json::to_string(summary.security_hub_id.as_ref()?).ok()?;
+    if value.chars().count() > MAX_BUTTON_VALUE {
+        tracing::warn!("Finding {} is too long for the workflow buttons", summary.uid);
+        return None;
+    }
+
+    let mut buttons = Vec::new();
+    if summary.status.eq_ignore_ascii_case("New") {
+        buttons.push(workflow_button("Acknowledge", ACKNOWLEDGE_ACTION_ID, &value, None));
+    }
+    buttons.push(workflow_button("Suppress", SUPPRESS_ACTION_ID, &value, Some("danger")));
+    buttons.push(workflow_button("Resolve", RESOLVE_ACTION_ID, &value, Some("primary")));
+
+    Some(json!({
+        "type": "actions",
+        "block_id": WORKFLOW_BLOCK_ID,
+        "elements": buttons
+    }))
+}
+
+fn workflow_button(text: &str, action_id: &str, value: &str, style: Option<&str>) -> Value {
+    let mut button = json!({
+        "type": "button",
+        "text": {
+            "type": "plain_text",
+            "text": text,
+            "emoji": true
+        },
+        "action_id": action_id,
+        "value": value
     });
+    if let Some(style) = style {
+        button["style"] = json!(style);
+    }
+    button
+}
 
-    // Make the POST request with JSON
-    let response = client
-        .post(url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json; charset=utf-8")
-        .json(&payload)
-        .send()
-        .await?;
-
-    // Check response status
-    if response.status().is_success() {
-        let response_body: Value = response.json().await?;
-        if response_body["ok"].as_bool().unwrap_or(false) {
-            Ok(())
-        } else {
-            let error = response_body["error"]
-                .as_str()
-                .unwrap_or("Unknown error");
-            Err(format!("Slack API error: {}", error).into())
+fn link_button(text: &str, action_id: &str, url: String) -> Value {
+    json!({
+        "type": "button",
+        "text": {
+            "type": "plain_text",
+            "text": text.chars().take(75).collect::<String>(),
+            "emoji": true
+        },
+        "action_id": action_id,
+        "url": url
+    })
+}
+
+/// Lists the domains, hostnames and OSINT indicators of the finding, defanged so
+/// Slack does not render them as links, along with the connection details.
+pub fn indicator_block(indicators: &Indicators) -> Option<Value> {
+    if indicators.is_empty() {
+        return None;
+    }
+
+    let defanged = |values: &[String]| values.iter()
+        .map(|v| format!("`{}`", defang(v)))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut lines = vec![":mag: *Indicators*".to_string()];
+    if !indicators.domains.is_empty() {
+        lines.push(format!("Domains: {}", defanged(&indicators.domains)));
+    }
+    if !indicators.hostnames.is_empty() {
+        lines.push(format!("Hostnames: {}", defanged(&indicators.hostnames)));
+    }
+    if !indicators.protocols.is_empty() {
+        let mut line = format!("Protocol: {}", indicators.protocols.join(", "));
+        if !indicators.directions.is_empty() {
+            line.push_str(&format!(" ({})", indicators.directions.join(", ")));
         }
-    } else {
-        Err(format!("HTTP error: {}", response.status()).into())
+        lines.push(line);
+    }
+    if let Some(blocked) = indicators.blocked {
+        lines.push(format!("Traffic blocked: {}", if blocked { "Yes" } else { "*No*" }));
+    }
+    for (kind, value) in &indicators.osint {
+        lines.push(format!("Threat intel: {} `{}`", kind, defang(value)));
+    }
+
+    Some(json!({
+        "type": "section",
+        "text": {
+            "type": "mrkdwn",
+            "text": lines.join("\n")
+        }
+    }))
+}
+
+fn resource_text(resource: &ResourceSummary) -> String {
+    let mut lines = vec![format!("*{}*  `{}`", resource.resource_type, resource.uid)];
+
+    let mut attributes = Vec::new();
+    if let Some(zone) = resource.zone.as_ref().or(resource.region.as_ref()) {
+        attributes.push(format!("Zone: {}", zone));
     }
-}
\ No newline at end of file
+    if let Some(image) = &resource.image {
+        attributes.push(format!("Image: `{}`", image));
+    }
+    if let Some(profile) = &resource.instance_profile {
+        // Show the profile name rather than the full ARN
+        let name = profile.rsplit('/').next().unwrap_or(profile);
+        attributes.push(format!("Instance profile: {}", name));
+    }
+    if !attributes.is_empty() {
+        lines.push(attributes.join("  |  "));
+    }
+
+    for interface in &resource.network_interfaces {
+        let mut line = format!(
+            "ENI {}  {}",
+            interface.uid.as_deref().unwrap_or("unknown"),
+            interface.ip.as_deref().unwrap_or("no IP"),
+        );
+        if !interface.security_groups.is_empty() {
+            line.push_str(&format!("  |  SG: {}", interface.security_groups.join(", ")));
+        }
+        lines.push(line);
+    }
+
+    if !resource.tags.is_empty() {
+        let tags: Vec<String> = resource.tags.iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        lines.push(format!("Tags: {}", tags.join(", ")));
+    }
+
+    lines.join("\n")
+}
+
+fn status_context(status: &str) -> Option<String> {
+    match status.to_ascii_lowercase().as_str() {
+        "new" | "unknown" | "" => None,
+        "resolved" => Some(":large_green_circle: *Resolved*".to_string()),
+        "suppressed" => Some(":white_circle: *Suppressed*".to_string()),
+        _ => Some(format!(":large_blue_circle: *Status: {}*", status)),
+    }
+}
+
+
+fn message_ref_from_response(response_body: &Value) -> Result<SlackMessageRef, Error> {
+    let channel = response_body["channel"].as_str().ok_or("Missing channel in Slack response")?;
+    let ts = response_body["ts"].as_str().ok_or("Missing ts in Slack response")?;
+
+    Ok(SlackMessageRef {
+        channel: channel.to_string(),
+        ts: ts.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::securityhub::FindingIdentifier;
+    use crate::struct_event::test_support;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn summary() -> FindingSummary {
+        test_support::summary(include_str!("../fixtures/guardduty_prod_critical.json"))
+    }
+
+    #[test]
+    fn test_new_finding_has_header_and_no_status() {
+        let blocks = finding_blocks(&summary(), &IconRegistry::default());
+
+        assert_eq!(blocks[0]["type"], "header");
+        assert!(blocks.iter().all(|b| b["type"] != "context"));
+    }
+
+    #[test]
+    fn test_description_icon_uses_registry_base_url() {
+        let blocks = finding_blocks(&summary(), &IconRegistry::new("https://d111111abcdef8.cloudfront.net/icons"));
+
+        assert_eq!(
+            blocks[1]["accessory"]["image_url"],
+            "https://d111111abcdef8.cloudfront.net/icons/Arch_Amazon-GuardDuty_64.png"
+        );
+    }
+
+    #[test]
+    fn test_resolved_finding_is_struck_through() {
+        let mut summary = summary();
+        summary.status = "Resolved".to_string();
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        assert_eq!(blocks[0]["type"], "section");
+        assert_eq!(blocks[0]["text"]["text"], format!("*~{}~*", summary.title));
+        assert_eq!(blocks[1]["type"], "context");
+        assert_eq!(blocks[1]["elements"][0]["text"], ":large_green_circle: *Resolved*");
+    }
+
+    #[test]
+    fn test_account_name_ou_path_and_tags() {
+        let mut summary = summary();
+        summary.account_info = Some(AccountInfo {
+            name: Some("payments-prod".to_string()),
+            ou_path: vec!["Workloads".to_string(), "Prod".to_string()],
+            tags: [("owner".to_string(), "team-payments".to_string())].into(),
+        });
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        let details = blocks.iter().find(|b| b["type"] == "rich_text").unwrap();
+        assert_eq!(details["elements"][0]["elements"][4]["text"], "\n• Account: payments-prod (111111111111)");
+        let context = blocks.iter().find(|b| b["type"] == "context").unwrap();
+        assert_eq!(
+            context["elements"][0]["text"],
+            "OU: Workloads / Prod  |  Account tags: `owner=team-payments`"
+        );
+    }
+
+    #[test]
+    fn test_owners_are_mentioned() {
+        let mut summary = summary();
+        summary.owners = vec!["S0123ABC".to_string(), "U0456DEF".to_string()];
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        assert!(blocks.iter().any(|b| {
+            b["text"]["text"] == ":bust_in_silhouette: *Owner:* <!subteam^S0123ABC> <@U0456DEF>"
+        }));
+    }
+
+    #[test]
+    fn test_risk_score_and_factors() {
+        let mut summary = summary();
+        summary.risk = Some(crate::risk::RiskModel::default().score(&summary));
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        assert!(blocks.iter().any(|b| {
+            b["elements"][0]["text"] == ":dart: *Risk score: 90/100*  |  Critical severity +70 · env=prod +20"
+        }));
+    }
+
+    #[test]
+    fn test_workflow_buttons_carry_finding_identifier() {
+        let summary = summary();
+        let block = workflow_actions(&summary).unwrap();
+        let action_ids: Vec<&str> = block["elements"].as_array().unwrap().iter()
+            .map(|b| b["action_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(action_ids, vec![ACKNOWLEDGE_ACTION_ID, SUPPRESS_ACTION_ID, RESOLVE_ACTION_ID]);
+
+        let finding: FindingIdentifier = serde_json::from_str(block["elements"][0]["value"].as_str().unwrap()).unwrap();
+        assert_eq!(finding, FindingIdentifier::Ocsf {
+            metadata_uid: "arn:aws:securityhub:us-east-1:111111111111:finding/5e0b2a1c-7d3f-4e2a-9b1c-0f2e3d4c5b6a".to_string(),
+        });
+
+        let mut notified = summary.clone();
+        notified.status = "Notified".to_string();
+        assert_eq!(workflow_actions(&notified).unwrap()["elements"].as_array().unwrap().len(), 2);
+
+        let mut resolved = summary;
+        resolved.status = "Resolved".to_string();
+        assert!(workflow_actions(&resolved).is_none());
+    }
+
+    #[test]
+    fn test_in_progress_finding_shows_status() {
+        let mut summary = summary();
+        summary.status = "In Progress".to_string();
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        assert_eq!(blocks[0]["type"], "header");
+        assert_eq!(blocks[1]["elements"][0]["text"], ":large_blue_circle: *Status: In Progress*");
+    }
+
+    #[test]
+    fn test_message_ref_from_response() {
+        let response = json!({"ok": true, "channel": "C123", "ts": "1772460312.000100"});
+        assert_eq!(
+            message_ref_from_response(&response).unwrap(),
+            SlackMessageRef { channel: "C123".to_string(), ts: "1772460312.000100".to_string() }
+        );
+        assert!(message_ref_from_response(&json!({"ok": true})).is_err());
+    }
+
+    fn client(server: &MockServer) -> SlackClient {
+        SlackClient::new("xoxb-test".to_string())
+            .with_api_url(server.uri())
+            .with_retry_policy(RetryPolicy {
+                max_retries: 3,
+                base_delay: Duration::from_millis(10),
+                max_delay: Duration::from_millis(50),
+                retry_posts: true,
+            })
+    }
+
+    fn posted() -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({"ok": true, "channel": "C123", "ts": "1.000100"}))
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let policy = RetryPolicy::default();
+        for attempt in 0..20 {
+            assert!(policy.backoff(attempt) <= policy.max_delay);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_after_429() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(posted())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let start = std::time::Instant::now();
+        let message = client(&server).post_slack_message("#sec", &summary()).await.unwrap();
+
+        assert_eq!(message.ts, "1.000100");
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_retries_ratelimited_and_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": false, "error": "ratelimited"})))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(posted())
+            .mount(&server)
+            .await;
+
+        let message = SlackMessageRef { channel: "C123".to_string(), ts: "1.000100".to_string() };
+        client(&server).update_slack_message_with_blocks(&message, json!([])).await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_posted_messages_are_retried_after_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(500))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(posted())
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let message = client(&server).post_slack_message("#sec", &summary()).await.unwrap();
+        assert_eq!(message.ts, "1.000100");
+    }
+
+    #[tokio::test]
+    async fn test_posted_messages_are_not_retried_when_post_retries_are_off() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat.postMessage"))
+            .respond_with(ResponseTemplate::new(503))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(&server).with_retry_policy(RetryPolicy {
+            max_retries: 3,
+            retry_posts: false,
+            ..Default::default()
+        });
+        let err = client.post_slack_message("#sec", &summary()).await.unwrap_err();
+        assert!(err.to_string().contains("503"));
+    }
+
+    #[tokio::test]
+    async fn test_retry_after_is_capped() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "3600"))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(posted())
+            .mount(&server)
+            .await;
+
+        let start = std::time::Instant::now();
+        client(&server).post_slack_message("C123", &summary()).await.unwrap();
+        // Retried once the channel's rate limit allows, not after an hour
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_no_retry_past_deadline() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = client(&server)
+            .with_deadline(SystemTime::now() + Duration::from_secs(3))
+            .post_slack_message("#sec", &summary())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("no time left to retry"));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_is_shared_by_channel_name_and_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(posted())
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = client(&server);
+        let start = std::time::Instant::now();
+        let message = client.post_slack_message("#sec", &summary()).await.unwrap();
+        client.post_thread_reply(&message, "Reopened").await.unwrap();
+
+        assert!(start.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+            .expect(4)
+            .mount(&server)
+            .await;
+
+        let err = client(&server).post_slack_message("#sec", &summary()).await.unwrap_err();
+        assert!(err.to_string().contains("gave up after 3 retries"));
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({"ok": false, "error": "channel_not_found"})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let err = client(&server).post_slack_message("#sec", &summary()).await.unwrap_err();
+        assert!(err.to_string().contains("channel_not_found"));
+    }
+
+    #[test]
+    fn test_resource_block_shows_network_details() {
+        let blocks = resource_blocks(&summary().resources);
+        let text = blocks[0]["text"]["text"].as_str().unwrap();
+
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(
+            text,
+            "*AWS::EC2::Instance*  `arn:aws:ec2:us-east-1:111111111111:instance/i-0abc123def4567890`\n\
+             Zone: us-east-1a  |  Image: `ami-0123456789abcdef0`  |  Instance profile: web-server\n\
+             ENI eni-0123456789abcdef0  10.0.1.25  |  SG: web-server-sg (sg-0a1b2c3d4e5f67890)\n\
+             Tags: env=prod, team=payments"
+        );
+    }
+
+    #[test]
+    fn test_resources_past_limit_are_collapsed() {
+        let resources: Vec<ResourceSummary> = (0..8)
+            .map(|i| ResourceSummary {
+                uid: format!("bucket-{}", i),
+                resource_type: "AWS::S3::Bucket".to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let blocks = resource_blocks(&resources);
+
+        assert_eq!(blocks.len(), MAX_RENDERED_RESOURCES + 1);
+        assert_eq!(blocks[0]["text"]["text"], "*AWS::S3::Bucket*  `bucket-0`");
+        assert_eq!(blocks[MAX_RENDERED_RESOURCES]["elements"][0]["text"], "+3 more resources");
+    }
+
+    #[test]
+    fn test_indicator_block_is_defanged() {
+        let block = indicator_block(&summary().indicators).unwrap();
+
+        assert_eq!(
+            block["text"]["text"],
+            ":mag: *Indicators*\n\
+             Domains: `c2[.]evil-domain[.]com`\n\
+             Hostnames: `c2[.]evil-domain[.]com`\n\
+             Protocol: UDP (Outbound)\n\
+             Traffic blocked: *No*\n\
+             Threat intel: Domain `c2[.]evil-domain[.]com`"
+        );
+        assert!(indicator_block(&Indicators::default()).is_none());
+    }
+
+    #[test]
+    fn test_console_actions_link_finding_service_and_resource() {
+        let actions = console_actions(&summary());
+        let buttons = actions["elements"].as_array().unwrap();
+
+        assert_eq!(buttons.len(), 3);
+        assert!(buttons[0]["url"].as_str().unwrap()
+            .starts_with("https://us-east-1.console.aws.amazon.com/securityhub/home?region=us-east-1#/findings?search=Id%3D"));
+        assert_eq!(buttons[1]["text"]["text"], "Open GuardDuty");
+        assert_eq!(buttons[1]["url"], "https://us-east-1.console.aws.amazon.com/guardduty/home?region=us-east-1");
+        assert_eq!(
+            buttons[2]["url"],
+            "https://us-east-1.console.aws.amazon.com/ec2/home?region=us-east-1#InstanceDetails:instanceId=i-0abc123def4567890"
+        );
+    }
+
+    #[test]
+    fn test_console_actions_in_govcloud() {
+        let mut summary = summary();
+        summary.region = "us-gov-west-1".to_string();
+        summary.partition = "aws-us-gov".to_string();
+        summary.web_rule = None;
+        summary.resources.clear();
+
+        let actions = console_actions(&summary);
+        let buttons = actions["elements"].as_array().unwrap();
+
+        assert_eq!(buttons.len(), 1);
+        assert!(buttons[0]["url"].as_str().unwrap()
+            .starts_with("https://console.amazonaws-us-gov.com/securityhub/home?region=us-gov-west-1#"));
+    }
+
+    #[test]
+    fn test_long_description_continues_in_sections() {
+        let mut summary = summary();
+        summary.title = "T".repeat(400);
+        summary.description = "Suspicious DNS activity. ".repeat(300);
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        assert_eq!(blocks[0]["text"]["text"].as_str().unwrap().chars().count(), blockkit::MAX_HEADER_TEXT);
+        assert_eq!(blocks[1]["accessory"]["type"], "image");
+        assert_eq!(blocks[2]["type"], "section");
+        assert!(blocks[2].get("accessory").is_none());
+        assert_eq!(
+            format!("{}{}{}", blocks[1]["text"]["text"].as_str().unwrap(), blocks[2]["text"]["text"].as_str().unwrap(), blocks[3]["text"]["text"].as_str().unwrap()),
+            summary.description
+        );
+        assert!(blockkit::validate(&blocks).is_ok());
+    }
+
+    #[test]
+    fn test_huge_description_keeps_workflow_buttons() {
+        let mut summary = summary();
+        summary.description = "Suspicious DNS activity. ".repeat(10_000);
+        let blocks = finding_blocks(&summary, &IconRegistry::default());
+
+        let sections = blocks.iter().filter(|b| b["type"] == "section" && b["text"]["text"].as_str().unwrap_or("").starts_with("Suspicious")).count();
+        assert_eq!(sections, blockkit::MAX_TEXT_SECTIONS);
+        assert!(blocks.iter().any(|b| b["block_id"] == WORKFLOW_BLOCK_ID));
+        assert_eq!(blocks.last().unwrap()["type"], "divider");
+        assert!(blockkit::validate(&blocks).is_ok());
+    }
+
+    proptest::proptest! {
+        #![proptest_config(proptest::prelude::ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_finding_blocks_respect_block_kit_limits(
+            title in "\\PC{1,400}",
+            description in "\\PC{0,7000}",
+            resources in 0usize..60,
+            closed in proptest::bool::ANY,
+        ) {
+            let mut summary = summary();
+            summary.title = title;
+            summary.description = description;
+            summary.status = if closed { "Resolved" } else { "New" }.to_string();
+            summary.resources = vec![summary.resources[0].clone(); resources];
+
+            let blocks = finding_blocks(&summary, &IconRegistry::default());
+            proptest::prop_assert!(blockkit::validate(&blocks).is_ok(), "{:?}", blockkit::validate(&blocks));
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use async_trait::async_trait;
+use aws_config::SdkConfig;
+use aws_sdk_dynamodb::types::AttributeValue;
+use aws_sdk_dynamodb::Client as DynamoDbClient;
+use lambda_runtime::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use crate::severity::Severity;
+use crate::notifier::MessageRef;
+
+/// Last notified state of a finding, keyed by `finding_info.uid`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FindingState {
+    pub uid: String,
+    pub severity: Severity,
+    pub status: String,
+    pub fingerprint: String,
+    /// Unix timestamp (seconds) of the last time the finding was posted.
+    pub last_notified: i64,
+    /// Messages the finding was posted as, updated in place on status changes.
+    #[serde(default)]
+    pub messages: Vec<MessageRef>,
+}
+
+/// Persistence for the notification state of findings.
+#[async_trait]
+pub trait FindingStateStore: Send + Sync {
+    async fn get(&self, uid: &str) -> Result<Option<FindingState>, Error>;
+    async fn put(&self, state: &FindingState) -> Result<(), Error>;
+}
+
+/// Builds the configured state store: `DEDUP_TABLE_NAME` selects DynamoDB and
+/// `DEDUP_STATE_FILE` a local JSON file. Returns `None` when deduplication is disabled.
+pub fn from_env(config: &SdkConfig) -> Option<Box<dyn FindingStateStore>> {
+    if let Ok(table_name) = std::env::var("DEDUP_TABLE_NAME") {
+        Some(Box::new(DynamoDbStateStore::new(DynamoDbClient::new(config), table_name)))
+    } else if let Ok(path) = std::env::var("DEDUP_STATE_FILE") {
+        Some(Box::new(FileStateStore::new(path)))
+    } else {
+        None
+    }
+}
+
+/// DynamoDB table with a string partition key `finding_uid` and the state as JSON in `state`.
+pub struct DynamoDbStateStore {
+    client: DynamoDbClient,
+    table_name: String,
+}
+
+impl DynamoDbStateStore {
+    pub fn new(client: DynamoDbClient, table_name: String) -> Self {
+        Self { client, table_name }
+    }
+}
+
+#[async_trait]
+impl FindingStateStore for DynamoDbStateStore {
+    async fn get(&self, uid: &str) -> Result<Option<FindingState>, Error> {
+        let response = self.client
+            .get_item()
+            .table_name(&self.table_name)
+            .key("finding_uid", AttributeValue::S(uid.to_string()))
+            .consistent_read(true)
+            .send()
+            .await?;
+
+        let state = response.item()
+            .and_then(|item| item.get("state"))
+            .and_then(|value| value.as_s().ok());
+
+        match state {
+            Some(json) => Ok(Some(serde_json::from_str(json)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn put(&self, state: &FindingState) -> Result<(), Error> {
+        self.client
+            .put_item()
+            .table_name(&self.table_name)
+            .item("finding_uid", AttributeValue::S(state.uid.clone()))
+            .item("state", AttributeValue::S(serde_json::to_string(state)?))
+            .item("updated_at", AttributeValue::N(state.last_notified.to_string()))
+            .send()
+            .await?;
+
+        Ok(())
+    }
+}
+
+/// In-memory store used by tests.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MemoryStateStore {
+    states: Mutex<HashMap<String, FindingState>>,
+}
+
+#[cfg(test)]
+#[async_trait]
+impl FindingStateStore for MemoryStateStore {
+    async fn get(&self, uid: &str) -> Result<Option<FindingState>, Error> {
+        Ok(self.states.lock().unwrap().get(uid).cloned())
+    }
+
+    async fn put(&self, state: &FindingState) -> Result<(), Error> {
+        self.states.lock().unwrap().insert(state.uid.clone(), state.clone());
+        Ok(())
+    }
+}
+
+/// Store backed by a JSON file mapping finding uid to state.
+pub struct FileStateStore {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+
+impl FileStateStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into(), lock: Mutex::new(()) }
+    }
+
+    fn read_all(&self) -> Result<HashMap<String, FindingState>, Error> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) if contents.trim().is_empty() => Ok(HashMap::new()),
+            Ok(contents) => Ok(serde_json::from_str(&contents)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HashMap::new()),
+            Err(e) => Err(format!("Failed to read state file '{}': {}", self.path.display(), e).into()),
+        }
+    }
+}
+
+#[async_trait]
+impl FindingStateStore for FileStateStore {
+    async fn get(&self, uid: &str) -> Result<Option<FindingState>, Error> {
+        let _guard = self.lock.lock().unwrap();
+        Ok(self.read_all()?.remove(uid))
+    }
+
+    async fn put(&self, state: &FindingState) -> Result<(), Error> {
+        let _guard = self.lock.lock().unwrap();
+        let mut states = self.read_all()?;
+        states.insert(state.uid.clone(), state.clone());
+        std::fs::write(&self.path, serde_json::to_string_pretty(&states)?)
+            .map_err(|e| format!("Failed to write state file '{}': {}", self.path.display(), e))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(uid: &str, status: &str) -> FindingState {
+        FindingState {
+            uid: uid.to_string(),
+            severity: Severity::High,
+            status: status.to_string(),
+            fingerprint: "abc".to_string(),
+            last_notified: 1_700_000_000,
+            messages: vec![MessageRef {
+                sink: "slack".to_string(),
+                channel: "C123".to_string(),
+                id: "1700000000.000100".to_string(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_memory_store_round_trip() {
+        let store = MemoryStateStore::default();
+        assert_eq!(store.get("f-1").await.unwrap(), None);
+
+        store.put(&state("f-1", "New")).await.unwrap();
+        store.put(&state("f-1", "Resolved")).await.unwrap();
+
+        assert_eq!(store.get("f-1").await.unwrap(), Some(state("f-1", "Resolved")));
+    }
+
+    #[test]
+    fn test_state_with_slack_only_messages() {
+        let stored: FindingState = serde_json::from_str(r#"{
+            "uid": "f-1",
+            "severity": "High",
+            "status": "New",
+            "fingerprint": "abc",
+            "last_notified": 1700000000,
+            "messages": [{"channel": "C123", "ts": "1700000000.000100"}]
+        }"#).unwrap();
+
+        assert_eq!(stored, state("f-1", "New"));
+    }
+
+    #[tokio::test]
+    async fn test_file_store_persists_between_instances() {
+        let path = std::env::temp_dir().join(format!("finding-state-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        FileStateStore::new(&path).put(&state("f-1", "New")).await.unwrap();
+        FileStateStore::new(&path).put(&state("f-2", "New")).await.unwrap();
+
+        let store = FileStateStore::new(&path);
+        assert_eq!(store.get("f-1").await.unwrap(), Some(state("f-1", "New")));
+        assert_eq!(store.get("f-2").await.unwrap(), Some(state("f-2", "New")));
+        assert_eq!(store.get("f-3").await.unwrap(), None);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,12 +1,67 @@
 use serde::{Deserialize, Serialize};
 use chrono::DateTime;
-
-#[derive(Debug, Serialize, Deserialize)]
+use lambda_runtime::{tracing, Error};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use crate::accounts::AccountInfo;
+use crate::asff::{AsffDetail, AsffFinding};
+use crate::compliance::{ControlSummary, FindingCategory};
+use crate::indicators::Indicators;
+use crate::links::{self, Partition};
+use crate::products::ProductDetails;
+use crate::risk::RiskScore;
+use crate::securityhub::FindingIdentifier;
+use crate::severity::Severity;
+
+/// Detail type of the OCSF "Findings Imported V2" events.
+pub const OCSF_DETAIL_TYPE: &str = "Findings Imported V2";
+/// Prefix of the legacy ASFF events ("Security Hub Findings - Imported", "- Custom Action").
+pub const ASFF_DETAIL_TYPE_PREFIX: &str = "Security Hub Findings - ";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Detail {
     pub findings: Option<Vec<Finding>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Parses the findings of an EventBridge event, normalizing legacy ASFF findings to OCSF
+/// based on the event `detail-type`.
+pub fn parse_findings(detail_type: &str, detail: Value) -> Result<Vec<Finding>, Error> {
+    if detail_type.starts_with(ASFF_DETAIL_TYPE_PREFIX) {
+        let detail: AsffDetail = serde_json::from_value(detail)
+            .map_err(|e| format!("Failed to parse ASFF detail: {}", e))?;
+        let findings = detail.findings.ok_or("Missing findings in detail")?;
+        Ok(findings.iter().map(|f| f.to_ocsf()).collect())
+    } else {
+        if detail_type != OCSF_DETAIL_TYPE {
+            tracing::warn!("Unexpected detail-type '{}', parsing as OCSF", detail_type);
+        }
+        let detail: Detail = serde_json::from_value(detail)
+            .map_err(|e| format!("Failed to parse detail: {}", e))?;
+        detail.findings.ok_or_else(|| "Missing findings in detail".into())
+    }
+}
+
+/// Reads the findings of a whole EventBridge event, or of a single OCSF or ASFF finding.
+pub fn findings_from_json(value: Value) -> Result<Vec<Finding>, Error> {
+    if let Some(detail_type) = value.get("detail-type").and_then(Value::as_str) {
+        let detail_type = detail_type.to_string();
+        let detail = value.get("detail").cloned().ok_or("Missing detail in event")?;
+        return parse_findings(&detail_type, detail);
+    }
+
+    // ASFF findings use PascalCase fields, OCSF findings snake_case
+    if value.get("SchemaVersion").is_some() || value.get("AwsAccountId").is_some() {
+        let finding: AsffFinding = serde_json::from_value(value)
+            .map_err(|e| format!("Failed to parse ASFF finding: {}", e))?;
+        return Ok(vec![finding.to_ocsf()]);
+    }
+
+    let finding: Finding = serde_json::from_value(value)
+        .map_err(|e| format!("Failed to parse finding: {}", e))?;
+    Ok(vec![finding])
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Finding {
     pub activity_id: Option<i32>,
     pub activity_name: Option<String>,
@@ -15,6 +70,9 @@ pub struct Finding {
     pub class_name: Option<String>,
     pub class_uid: Option<i32>,
     pub cloud: Option<Cloud>,
+    /// Comment recorded with the latest workflow update.
+    pub comment: Option<String>,
+    pub compliance: Option<Compliance>,
     pub count: Option<i32>,
     pub evidences: Option<Vec<Evidence>>,
     pub finding_info: Option<FindingInfo>,
@@ -31,9 +89,14 @@ pub struct Finding {
     pub type_name: Option<String>,
     pub type_uid: Option<i32>,
     pub vendor_attributes: Option<VendorAttributes>,
+    pub vulnerabilities: Option<Vec<Vulnerability>>,
+    /// Set on findings normalized from a legacy ASFF event, which Security Hub updates by
+    /// `Id` and `ProductArn` instead of `metadata.uid`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub from_asff: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Cloud {
     pub account: Option<Account>,
     pub cloud_partition: Option<String>,
@@ -41,7 +104,7 @@ pub struct Cloud {
     pub region: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Account {
     #[serde(rename = "type")]
     pub account_type: Option<String>,
@@ -49,21 +112,81 @@ pub struct Account {
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Result of a Security Hub CSPM control check.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Compliance {
+    /// Control ID, e.g. `S3.8`.
+    pub control: Option<String>,
+    pub requirements: Option<Vec<String>>,
+    /// ARNs of the standards the control belongs to.
+    pub standards: Option<Vec<String>>,
+    pub status: Option<String>,
+    pub status_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Evidence {
+    pub actor: Option<Actor>,
+    pub api: Option<Api>,
     pub connection_info: Option<ConnectionInfo>,
     pub data: Option<EvidenceData>,
     pub query: Option<Query>,
+    pub src_endpoint: Option<NetworkEndpoint>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Actor {
+    pub user: Option<User>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct User {
+    pub name: Option<String>,
+    #[serde(rename = "type")]
+    pub user_type: Option<String>,
+    pub uid: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Api {
+    pub operation: Option<String>,
+    pub service: Option<ApiService>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApiService {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkEndpoint {
+    pub autonomous_system: Option<AutonomousSystem>,
+    pub domain: Option<String>,
+    pub ip: Option<String>,
+    pub location: Option<GeoLocation>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutonomousSystem {
+    pub name: Option<String>,
+    pub number: Option<i64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GeoLocation {
+    pub city: Option<String>,
+    pub country: Option<String>,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConnectionInfo {
     pub direction: Option<String>,
     pub direction_id: Option<i32>,
     pub protocol_name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct EvidenceData {
     pub blocked: Option<bool>,
     pub domain: Option<String>,
@@ -72,12 +195,12 @@ pub struct EvidenceData {
     pub vpc_owner_account_id: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Query {
     pub hostname: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct FindingInfo {
     pub analytic: Option<Analytic>,
     pub created_time: Option<i64>,
@@ -96,7 +219,7 @@ pub struct FindingInfo {
     pub uid_alt: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Analytic {
     #[serde(rename = "type")]
     pub analytic_type: Option<String>,
@@ -104,12 +227,12 @@ pub struct Analytic {
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Product {
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Metadata {
     pub extensions: Option<Vec<Extension>>,
     pub product: Option<MetadataProduct>,
@@ -118,14 +241,14 @@ pub struct Metadata {
     pub version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Extension {
     pub name: Option<String>,
     pub uid: Option<String>,
     pub version: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct MetadataProduct {
     pub feature: Option<Feature>,
     pub name: Option<String>,
@@ -133,12 +256,12 @@ pub struct MetadataProduct {
     pub vendor_name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Feature {
     pub name: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Osint {
     pub name: Option<String>,
     #[serde(rename = "type")]
@@ -147,15 +270,18 @@ pub struct Osint {
     pub value: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Remediation {
     pub desc: Option<String>,
     pub references: Option<Vec<String>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Resource {
     pub cloud_partition: Option<String>,
+    /// Product-specific details, e.g. the principal and actions of Access Analyzer findings.
+    pub data: Option<Value>,
+    pub data_classifications: Option<Vec<DataClassification>>,
     pub device: Option<Device>,
     pub owner: Option<Owner>,
     pub region: Option<String>,
@@ -166,7 +292,7 @@ pub struct Resource {
     pub zone: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Device {
     pub image: Option<Image>,
     pub instance_profile: Option<InstanceProfile>,
@@ -180,57 +306,135 @@ pub struct Device {
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Image {
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct InstanceProfile {
     pub uid: Option<String>,
     pub uid_alt: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NetworkInterface {
     pub ip: Option<String>,
     pub security_groups: Option<Vec<SecurityGroup>>,
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct SecurityGroup {
     pub name: Option<String>,
     pub uid: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Owner {
     pub account: Option<Account>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Tag {
     pub name: Option<String>,
     pub value: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Sensitive data discovered in a resource, e.g. by Macie.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DataClassification {
+    pub category: Option<String>,
+    pub discovery_details: Option<Vec<DiscoveryDetails>>,
+    pub total: Option<i64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DiscoveryDetails {
+    pub count: Option<i64>,
+    #[serde(rename = "type")]
+    pub detail_type: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Vulnerability {
+    pub affected_packages: Option<Vec<AffectedPackage>>,
+    pub cve: Option<Cve>,
+    pub fix_available: Option<bool>,
+    pub is_exploit_available: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cve {
+    pub cvss: Option<Vec<Cvss>>,
+    pub uid: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Cvss {
+    pub base_score: Option<f64>,
+    pub vector_string: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AffectedPackage {
+    pub fixed_in_version: Option<String>,
+    pub name: Option<String>,
+    pub package_manager: Option<String>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct VendorAttributes {
     pub severity: Option<String>,
     pub severity_id: Option<i32>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FindingSummary {
+    pub uid: String,
     pub title: String,
     pub region: String,
+    pub partition: String,
     pub account: String,
+    /// Name, OU path and tags of the account, filled in by the account enricher.
+    #[serde(default)]
+    pub account_info: Option<AccountInfo>,
+    /// Slack user or user group IDs of the owners to mention.
+    #[serde(default)]
+    pub owners: Vec<String>,
     pub product_name: String,
+    pub product_arn: String,
     pub product_aws: String,
+    /// Identifies the finding in Security Hub updates, `None` when the event has no id.
+    #[serde(default)]
+    pub security_hub_id: Option<FindingIdentifier>,
     pub resource_id: String,
-    pub severity: String,
-    pub web_rule: String,
+    pub resources: Vec<ResourceSummary>,
+    pub resource_types: Vec<String>,
+    pub resource_tags: Vec<(String, String)>,
+    /// Finding types from `finding_info.types`, e.g. `TTPs/Command and Control/...`.
+    pub finding_types: Vec<String>,
+    pub indicators: Indicators,
+    #[serde(default)]
+    pub category: FindingCategory,
+    /// Security Hub CSPM control of compliance findings.
+    #[serde(default)]
+    pub control: Option<ControlSummary>,
+    /// Details rendered by the product-specific renderer.
+    #[serde(default)]
+    pub product_details: ProductDetails,
+    /// Risk score computed by the configured risk model.
+    #[serde(default)]
+    pub risk: Option<RiskScore>,
+    pub severity: Severity,
+    pub status: String,
+    /// Comment recorded with the latest workflow update, e.g. by the Slack buttons.
+    #[serde(default)]
+    pub comment: Option<String>,
+    /// Console of the AWS service that generated the finding, `None` for third-party products.
+    pub web_rule: Option<String>,
     pub button_text: String,
     pub description: String,
     pub remediation: String,
@@ -238,8 +442,77 @@ pub struct FindingSummary {
     pub last_seen: String,
 }
 
+/// Display attributes of one affected resource.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceSummary {
+    pub uid: String,
+    pub resource_type: String,
+    pub region: Option<String>,
+    pub zone: Option<String>,
+    pub tags: Vec<(String, String)>,
+    pub image: Option<String>,
+    pub instance_profile: Option<String>,
+    pub network_interfaces: Vec<NetworkInterfaceSummary>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NetworkInterfaceSummary {
+    pub uid: Option<String>,
+    pub ip: Option<String>,
+    /// Security groups rendered as `name (sg-id)`.
+    pub security_groups: Vec<String>,
+}
+
+impl ResourceSummary {
+    pub fn from_resource(resource: &Resource) -> Self {
+        let device = resource.device.as_ref();
+
+        let tags = resource.tags.as_deref().unwrap_or_default()
+            .iter()
+            .filter_map(|t| Some((t.name.clone()?, t.value.clone().unwrap_or_default())))
+            .collect();
+
+        let network_interfaces = device
+            .and_then(|d| d.network_interfaces.as_deref())
+            .unwrap_or_default()
+            .iter()
+            .map(|ni| NetworkInterfaceSummary {
+                uid: ni.uid.clone(),
+                ip: ni.ip.clone(),
+                security_groups: ni.security_groups.as_deref().unwrap_or_default()
+                    .iter()
+                    .filter_map(|sg| match (&sg.name, &sg.uid) {
+                        (Some(name), Some(uid)) => Some(format!("{} ({})", name, uid)),
+                        (Some(name), None) => Some(name.clone()),
+                        (None, Some(uid)) => Some(uid.clone()),
+                        (None, None) => None,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self {
+            uid: resource.uid.clone().unwrap_or_else(|| "unknown-resource".to_string()),
+            resource_type: resource.resource_type.clone().unwrap_or_else(|| "Unknown".to_string()),
+            region: resource.region.clone(),
+            zone: resource.zone.clone(),
+            tags,
+            image: device.and_then(|d| d.image.as_ref()).and_then(|i| i.uid.clone()),
+            instance_profile: device
+                .and_then(|d| d.instance_profile.as_ref())
+                .and_then(|p| p.uid.clone()),
+            network_interfaces,
+        }
+    }
+}
+
 impl FindingSummary {
     pub fn from_finding(finding: &Finding) -> Self {
+        // Extract the finding identifier, falling back to the Security Hub metadata uid
+        let explicit_uid = finding.finding_info.as_ref()
+            .and_then(|fi| fi.uid.as_deref())
+            .or_else(|| finding.metadata.as_ref().and_then(|m| m.uid.as_deref()));
+
         // Extract title
         let title = finding.finding_info.as_ref()
             .and_then(|fi| fi.title.as_deref())
@@ -272,7 +545,7 @@ impl FindingSummary {
             .and_then(|p| p.uid.as_deref())
             .unwrap_or("");
         
-        let product_aws = product_arn.split('/').last().unwrap_or("unknown").to_string();
+        let product_aws = product_arn.split('/').next_back().unwrap_or("unknown").to_string();
 
         // Extract resource_id from first resource
         let resource_id = finding.resources.as_ref()
@@ -281,6 +554,41 @@ impl FindingSummary {
             .unwrap_or("unknown-resource")
             .to_string();
 
+        let security_hub_id = if finding.from_asff {
+            explicit_uid.map(|id| FindingIdentifier::Asff { id: id.to_string(), product_arn: product_arn.to_string() })
+        } else {
+            finding.metadata.as_ref()
+                .and_then(|m| m.uid.clone())
+                .map(|metadata_uid| FindingIdentifier::Ocsf { metadata_uid })
+        };
+
+        let uid = match explicit_uid {
+            Some(uid) => uid.to_string(),
+            None => derived_uid(&[product_arn, &account, &region, &title, &resource_id]),
+        };
+
+        // Collect resource types and tags across all resources for routing
+        let all_resources = finding.resources.as_deref().unwrap_or_default();
+
+        let resource_types = all_resources.iter()
+            .filter_map(|r| r.resource_type.clone())
+            .collect();
+
+        let resource_tags = all_resources.iter()
+            .flat_map(|r| r.tags.as_deref().unwrap_or_default())
+            .filter_map(|t| Some((t.name.clone()?, t.value.clone().unwrap_or_default())))
+            .collect();
+
+        let resources = all_resources.iter()
+            .map(ResourceSummary::from_resource)
+            .collect();
+
+        let finding_types = finding.finding_info.as_ref()
+            .and_then(|fi| fi.types.clone())
+            .unwrap_or_default();
+
+        let indicators = Indicators::from_finding(finding);
+
         let description = finding.finding_info.as_ref()
             .and_then(|fi| fi.desc.as_deref())
             .unwrap_or("")
@@ -312,23 +620,53 @@ impl FindingSummary {
             })
             .unwrap_or_else(|| "Unknown".to_string());
 
-        // Extract severity
-        let severity = finding.severity.as_deref().unwrap_or("Unknown").to_string();
+        let product_details = ProductDetails::from_finding(finding, &product_name);
+        let category = FindingCategory::from_finding(finding);
+        let control = finding.compliance.as_ref().and_then(ControlSummary::from_compliance);
 
-        // Build web rule URL
-        let web_rule = format!("https://{}.console.aws.amazon.com/{}/", region, product_aws);
+        // Extract severity
+        let severity = Severity::from_finding(finding.severity.as_deref(), finding.severity_id);
+
+        // Extract workflow status
+        let status = finding.status.as_deref().unwrap_or("Unknown").to_string();
+        let comment = finding.comment.clone();
+
+        // Build the partition-aware service console URL
+        let partition = Partition::resolve(
+            finding.cloud.as_ref().and_then(|c| c.cloud_partition.as_deref()),
+            &region,
+        );
+        let web_rule = links::service_console_url(partition, &region, product_arn);
+        let partition = partition.as_str().to_string();
         
         // Button text is the product name
         let button_text = product_name.clone();
 
         Self {
+            uid,
             title,
             region,
+            partition,
             account,
+            account_info: None,
+            owners: Vec::new(),
             product_name,
+            product_arn: product_arn.to_string(),
             product_aws,
+            security_hub_id,
             resource_id,
+            resources,
+            resource_types,
+            resource_tags,
+            finding_types,
+            indicators,
+            category,
+            control,
+            product_details,
+            risk: None,
             severity,
+            status,
+            comment,
             web_rule,
             button_text,
             description,
@@ -337,6 +675,44 @@ impl FindingSummary {
             last_seen,
         }
     }
+
+    /// Account as `name (id)` when the account name is known, otherwise the bare id.
+    pub fn account_label(&self) -> String {
+        match self.account_info.as_ref().and_then(|info| info.name.as_deref()) {
+            Some(name) => format!("{} ({})", name, self.account),
+            None => self.account.clone(),
+        }
+    }
+}
+
+/// Identifier of a finding without a uid, derived from the fields that stay the same when
+/// the finding is re-emitted so deduplication still works per finding.
+fn derived_uid(fields: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for field in fields {
+        hasher.update(field.as_bytes());
+        hasher.update([0u8]);
+    }
+    format!("unknown-finding-{}", &hex::encode(hasher.finalize())[..16])
+}
+
+/// Findings parsed from the EventBridge events in `fixtures/`, shared by the tests.
+#[cfg(test)]
+pub mod test_support {
+    use super::*;
+    use serde_json::Value;
+
+    /// First finding of the event `fixture`.
+    pub fn finding(fixture: &str) -> Finding {
+        let event: Value = serde_json::from_str(fixture).unwrap();
+        let detail: Detail = serde_json::from_value(event["detail"].clone()).unwrap();
+        detail.findings.unwrap().remove(0)
+    }
+
+    /// Summary of the first finding of the event `fixture`.
+    pub fn summary(fixture: &str) -> FindingSummary {
+        FindingSummary::from_finding(&finding(fixture))
+    }
 }
 
 // pub async fn process_finding(finding: &Finding) -> Result<(), Error> {
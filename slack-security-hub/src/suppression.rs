@@ -0,0 +1,263 @@
+use aws_config::SdkConfig;
+use chrono::NaiveDate;
+use lambda_runtime::{tracing, Error};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::routing::matches_any;
+use crate::struct_event::FindingSummary;
+use crate::s3::{self, S3Uri};
+
+/// Mute rules for known-noisy findings. A finding matching an active rule is counted
+/// and logged but not delivered.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuppressionRules {
+    #[serde(default)]
+    pub rules: Vec<SuppressionRule>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuppressionRule {
+    pub name: String,
+    pub reason: String,
+    /// Last day (UTC) the rule is active.
+    pub expires: NaiveDate,
+    #[serde(rename = "match")]
+    pub criteria: SuppressionMatch,
+    /// Also set the workflow status of matching findings to SUPPRESSED in Security Hub.
+    #[serde(default)]
+    pub suppress_in_security_hub: bool,
+}
+
+/// Criteria of a suppression rule. Empty lists match anything; all non-empty criteria
+/// must match, and a rule needs at least one criterion.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SuppressionMatch {
+    #[serde(default)]
+    pub title_regex: Option<String>,
+    #[serde(skip)]
+    title_pattern: Option<Regex>,
+    #[serde(default)]
+    pub products: Vec<String>,
+    #[serde(default)]
+    pub accounts: Vec<String>,
+    /// Resource uid or ARN globs, where `*` matches any run of characters and `?` one.
+    #[serde(default)]
+    pub resources: Vec<String>,
+    /// Tag name to required value. A value of `*` only requires the tag to be present.
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+    /// Globs over `finding_info.types`.
+    #[serde(default)]
+    pub finding_types: Vec<String>,
+}
+
+impl SuppressionRules {
+    /// Loads the rules from `SUPPRESSION_RULES_FILE` (path) or `SUPPRESSION_RULES_S3_URI`
+    /// (`s3://bucket/key`). Files ending in `.yaml` or `.yml` are parsed as YAML, anything
+    /// else as JSON. Without either variable no finding is suppressed.
+    pub async fn from_env(config: &SdkConfig) -> Result<Self, Error> {
+        let (source, contents) = if let Ok(path) = std::env::var("SUPPRESSION_RULES_FILE") {
+            let contents = std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read suppression rules file '{}': {}", path, e))?;
+            (path, contents)
+        } else if let Ok(uri) = std::env::var("SUPPRESSION_RULES_S3_URI") {
+            let contents = read_s3_object(config, &uri).await
+                .map_err(|e| format!("Failed to read suppression rules from '{}': {}", uri, e))?;
+            (uri, contents)
+        } else {
+            return Ok(Self::default());
+        };
+
+        let rules = Self::parse(&contents, is_yaml(&source))?;
+
+        let today = chrono::Utc::now().date_naive();
+        for rule in rules.rules.iter().filter(|r| !r.is_active(today)) {
+            tracing::warn!("Suppression rule '{}' expired on {}", rule.name, rule.expires);
+        }
+
+        Ok(rules)
+    }
+
+    pub fn parse(contents: &str, yaml: bool) -> Result<Self, Error> {
+        let mut rules: Self = if yaml {
+            serde_yaml::from_str(contents)
+                .map_err(|e| format!("Failed to parse suppression rules: {}", e))?
+        } else {
+            serde_json::from_str(contents)
+                .map_err(|e| format!("Failed to parse suppression rules: {}", e))?
+        };
+
+        for rule in &mut rules.rules {
+            rule.criteria.compile()
+                .map_err(|e| format!("Invalid suppression rule '{}': {}", rule.name, e))?;
+        }
+
+        Ok(rules)
+    }
+
+    /// Returns the first active rule that matches the finding.
+    pub fn matching(&self, summary: &FindingSummary, today: NaiveDate) -> Option<&SuppressionRule> {
+        self.rules.iter().find(|rule| rule.is_active(today) && rule.criteria.matches(summary))
+    }
+}
+
+impl SuppressionRule {
+    pub fn is_active(&self, today: NaiveDate) -> bool {
+        today <= self.expires
+    }
+}
+
+impl SuppressionMatch {
+    fn compile(&mut self) -> Result<(), String> {
+        if let Some(pattern) = &self.title_regex {
+            self.title_pattern = Some(Regex::new(pattern).map_err(|e| e.to_string())?);
+        }
+
+        // A rule without criteria would mute every finding
+        if self.title_pattern.is_none()
+            && self.products.is_empty()
+            && self.accounts.is_empty()
+            && self.resources.is_empty()
+            && self.tags.is_empty()
+            && self.finding_types.is_empty()
+        {
+            return Err("at least one match criterion is required".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn matches(&self, summary: &FindingSummary) -> bool {
+        self.title_pattern.as_ref().is_none_or(|p| p.is_match(&summary.title))
+            && matches_any(&self.products, |p| {
+                p.eq_ignore_ascii_case(&summary.product_name) || p.eq_ignore_ascii_case(&summary.product_aws)
+            })
+            && matches_any(&self.accounts, |a| a == &summary.account)
+            && matches_any(&self.resources, |pattern| {
+                summary.resources.iter().any(|r| glob_match(pattern, &r.uid))
+            })
+            && self.tags.iter().all(|(name, value)| {
+                summary.resource_tags.iter().any(|(tag_name, tag_value)| {
+                    tag_name == name && (value == "*" || tag_value == value)
+                })
+            })
+            && matches_any(&self.finding_types, |pattern| {
+                summary.finding_types.iter().any(|t| glob_match(pattern, t))
+            })
+    }
+}
+
+/// Matches `text` against a glob where `*` matches any run of characters and `?` a
+/// single character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it currently absorbs up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, absorbed)) = backtrack {
+            p = star + 1;
+            t = absorbed + 1;
+            backtrack = Some((star, absorbed + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn is_yaml(source: &str) -> bool {
+    let source = source.to_ascii_lowercase();
+    source.ends_with(".yaml") || source.ends_with(".yml")
+}
+
+async fn read_s3_object(config: &SdkConfig, uri: &str) -> Result<String, Error> {
+    let location = S3Uri::object(uri)?;
+    let bytes = s3::get_object(&aws_sdk_s3::Client::new(config), &location.bucket, &location.key).await?
+        .ok_or("object not found")?;
+
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support::summary;
+
+    const RULES: &str = r#"
+rules:
+  - name: dev-inspector-expat
+    reason: Accepted risk until the base image upgrade (SEC-1234)
+    expires: 2026-12-31
+    match:
+      title_regex: "^CVE-2024-45491"
+      products: [inspector]
+      accounts: ["222222222222"]
+  - name: pentest-dns
+    reason: Scheduled penetration test
+    expires: 2026-03-31
+    suppress_in_security_hub: true
+    match:
+      resources: ["arn:aws:ec2:us-east-1:111111111111:instance/*"]
+      finding_types: ["TTPs/Command and Control/*"]
+"#;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("arn:aws:s3:::logs-*", "arn:aws:s3:::logs-prod"));
+        assert!(glob_match("*i-0abc*", "arn:aws:ec2:us-east-1:1:instance/i-0abc123"));
+        assert!(glob_match("i-????", "i-1234"));
+        assert!(glob_match("a*b*c", "aXXbYYbZc"));
+        assert!(!glob_match("arn:aws:s3:::logs-*", "arn:aws:s3:::audit"));
+        assert!(!glob_match("i-????", "i-12345"));
+    }
+
+    #[test]
+    fn test_yaml_rules_match_until_expiry() {
+        let rules = SuppressionRules::parse(RULES, true).unwrap();
+        let inspector = summary(include_str!("../fixtures/inspector_dev_high.json"));
+        let guardduty = summary(include_str!("../fixtures/guardduty_prod_critical.json"));
+
+        assert_eq!(rules.matching(&inspector, date("2026-10-16")).unwrap().name, "dev-inspector-expat");
+        assert!(rules.matching(&inspector, date("2027-01-01")).is_none());
+
+        let rule = rules.matching(&guardduty, date("2026-03-31")).unwrap();
+        assert_eq!(rule.name, "pentest-dns");
+        assert!(rule.suppress_in_security_hub);
+        assert!(rules.matching(&guardduty, date("2026-04-01")).is_none());
+    }
+
+    #[test]
+    fn test_tag_criteria_from_json() {
+        let rules = SuppressionRules::parse(r#"{"rules": [{
+            "name": "sandbox", "reason": "Sandbox accounts", "expires": "2030-01-01",
+            "match": {"tags": {"env": "dev"}}
+        }]}"#, false).unwrap();
+
+        let today = date("2026-10-16");
+        assert!(rules.matching(&summary(include_str!("../fixtures/inspector_dev_high.json")), today).is_some());
+        assert!(rules.matching(&summary(include_str!("../fixtures/guardduty_prod_critical.json")), today).is_none());
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        let empty = r#"{"rules": [{"name": "all", "reason": "x", "expires": "2030-01-01", "match": {}}]}"#;
+        assert!(SuppressionRules::parse(empty, false).is_err());
+
+        let bad_regex = r#"{"rules": [{"name": "re", "reason": "x", "expires": "2030-01-01", "match": {"title_regex": "("}}]}"#;
+        assert!(SuppressionRules::parse(bad_regex, false).is_err());
+    }
+}
@@ -0,0 +1,148 @@
+use async_trait::async_trait;
+use lambda_runtime::Error;
+use reqwest::Client;
+use serde_json::{json, Value};
+use crate::notifier::{MessageRef, Notifier};
+use crate::severity::Severity;
+use crate::struct_event::FindingSummary;
+
+/// Posts findings as Adaptive Cards to a Microsoft Teams incoming webhook.
+pub struct TeamsNotifier {
+    client: Client,
+    webhook_url: String,
+}
+
+impl TeamsNotifier {
+    pub fn new(webhook_url: String) -> Self {
+        Self {
+            client: Client::new(),
+            webhook_url,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for TeamsNotifier {
+    fn name(&self) -> &'static str {
+        "teams"
+    }
+
+    fn render(&self, summary: &FindingSummary) -> Value {
+        adaptive_card_message(summary)
+    }
+
+    async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+        let response = self.client
+            .post(&self.webhook_url)
+            .json(&adaptive_card_message(summary))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(Vec::new())
+        } else {
+            Err(format!("Teams webhook error: {}", response.status()).into())
+        }
+    }
+}
+
+/// Builds the webhook message wrapping an Adaptive Card for the finding.
+pub fn adaptive_card_message(summary: &FindingSummary) -> Value {
+    let title_color = match summary.severity {
+        Severity::Critical | Severity::Fatal | Severity::High => "Attention",
+        Severity::Medium => "Warning",
+        _ => "Default",
+    };
+
+    let mut actions = Vec::new();
+    if summary.remediation != "no_remediation" {
+        actions.push(json!({
+            "type": "Action.OpenUrl",
+            "title": "Remediations",
+            "url": summary.remediation
+        }));
+    }
+
+    json!({
+        "type": "message",
+        "attachments": [
+            {
+                "contentType": "application/vnd.microsoft.card.adaptive",
+                "contentUrl": null,
+                "content": {
+                    "$schema": "http://adaptivecards.io/schemas/adaptive-card.json",
+                    "type": "AdaptiveCard",
+                    "version": "1.4",
+                    "body": [
+                        {
+                            "type": "TextBlock",
+                            "text": summary.title,
+                            "weight": "Bolder",
+                            "size": "Medium",
+                            "color": title_color,
+                            "wrap": true
+                        },
+                        {
+                            "type": "TextBlock",
+                            "text": summary.description,
+                            "wrap": true
+                        },
+                        {
+                            "type": "FactSet",
+                            "facts": [
+                                { "title": "Product Name", "value": summary.product_name },
+                                { "title": "Severity", "value": summary.severity.to_string() },
+                                { "title": "Status", "value": summary.status },
+                                { "title": "Account", "value": summary.account_label() },
+                                { "title": "Region", "value": summary.region },
+                                { "title": "Resource Id", "value": summary.resource_id },
+                                { "title": "First Seen", "value": summary.first_seen },
+                                { "title": "Last Seen", "value": summary.last_seen }
+                            ]
+                        }
+                    ],
+                    "actions": actions
+                }
+            }
+        ]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support;
+    use wiremock::matchers::{body_partial_json, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn summary() -> FindingSummary {
+        test_support::summary(include_str!("../fixtures/guardduty_prod_critical.json"))
+    }
+
+    #[test]
+    fn test_adaptive_card_content() {
+        let summary = summary();
+        let message = adaptive_card_message(&summary);
+        let card = &message["attachments"][0]["content"];
+
+        assert_eq!(card["type"], "AdaptiveCard");
+        assert_eq!(card["body"][0]["text"], summary.title);
+        assert_eq!(card["body"][0]["color"], "Attention");
+        assert_eq!(card["body"][2]["facts"][1]["value"], "Critical");
+        assert_eq!(card["actions"][0]["url"], summary.remediation);
+    }
+
+    #[tokio::test]
+    async fn test_posts_card_to_webhook() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(json!({"type": "message"})))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = TeamsNotifier::new(server.uri());
+        assert!(notifier.notify(&summary()).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use lambda_runtime::Error;
+use reqwest::Client;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use crate::notifier::{MessageRef, Notifier};
+use crate::struct_event::FindingSummary;
+
+pub const SIGNATURE_HEADER: &str = "X-Signature-256";
+
+/// Posts the finding summary as JSON to a generic webhook. When a signing secret is
+/// configured the raw body is signed with HMAC-SHA256 in the `X-Signature-256` header.
+pub struct WebhookNotifier {
+    client: Client,
+    url: String,
+    signing_secret: Option<String>,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String, signing_secret: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url,
+            signing_secret,
+        }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn render(&self, summary: &FindingSummary) -> Value {
+        json!({
+            "type": "security_hub_finding",
+            "finding": summary
+        })
+    }
+
+    async fn notify(&self, summary: &FindingSummary) -> Result<Vec<MessageRef>, Error> {
+        let body = serde_json::to_vec(&self.render(summary))?;
+
+        let mut request = self.client
+            .post(&self.url)
+            .header("Content-Type", "application/json");
+        if let Some(secret) = &self.signing_secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+
+        let response = request.body(body).send().await?;
+
+        if response.status().is_success() {
+            Ok(Vec::new())
+        } else {
+            Err(format!("Webhook error: {}", response.status()).into())
+        }
+    }
+}
+
+/// Returns the `sha256=<hex>` signature of the body.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::struct_event::test_support;
+    use serde_json::Value;
+    use wiremock::{Mock, MockServer, Request, Respond, ResponseTemplate};
+
+    fn summary() -> FindingSummary {
+        test_support::summary(include_str!("../fixtures/inspector_dev_high.json"))
+    }
+
+    /// Accepts the request only when its signature matches the body.
+    struct VerifySignature(Option<&'static str>);
+
+    impl Respond for VerifySignature {
+        fn respond(&self, request: &Request) -> ResponseTemplate {
+            let signature = request.headers.get(SIGNATURE_HEADER).map(|v| v.to_str().unwrap().to_string());
+            let expected = self.0.map(|secret| sign(secret, &request.body));
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+
+            if signature == expected && body["finding"]["severity"] == "High" {
+                ResponseTemplate::new(200)
+            } else {
+                ResponseTemplate::new(401)
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_known_vector() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signed_delivery() {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(VerifySignature(Some("s3cr3t")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = WebhookNotifier::new(server.uri(), Some("s3cr3t".to_string()));
+        notifier.notify(&summary()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_unsigned_delivery() {
+        let server = MockServer::start().await;
+        Mock::given(wiremock::matchers::method("POST"))
+            .respond_with(VerifySignature(None))
+            .mount(&server)
+            .await;
+
+        WebhookNotifier::new(server.uri(), None).notify(&summary()).await.unwrap();
+
+        let wrong_secret = WebhookNotifier::new(server.uri(), Some("other".to_string()));
+        assert!(wrong_secret.notify(&summary()).await.is_err());
+    }
+}